@@ -0,0 +1,145 @@
+//! Checks a level file for unreachable diamonds, softlocks and sections that can only be
+//! passed by dying. Prints one JSON object per line and exits with 1 if any error was found
+//! or the search gave up before exploring every state.
+//!
+//! Usage: validate-level [--seed N] [--max-states N] [--generate SEED] [LEVEL_FILE]
+//!
+//! Without a level file, the built-in tower is checked. `--generate` checks a random tower
+//! as generated for the given seed instead.
+
+use sj6::generate::*;
+use sj6::level::*;
+use sj6::validate::*;
+use std::process::ExitCode;
+
+fn platform_json(level: &LevelDesc, index: usize) -> String {
+    let platform = &level.platforms[index];
+    format!(
+        r#"{{"index":{},"line":{},"x":{},"y":{},"w":{},"h":{}}}"#,
+        index,
+        platform.line,
+        platform.position.x,
+        platform.position.y,
+        platform.size.x,
+        platform.size.y
+    )
+}
+
+fn finding_json(level: &LevelDesc, finding: &Finding) -> String {
+    let severity = match finding.severity() {
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    };
+    let details = match finding {
+        Finding::Overlap { a, b, step } => format!(
+            r#""kind":"overlap","a":{},"b":{},"step":{}"#,
+            platform_json(level, *a),
+            platform_json(level, *b),
+            step
+        ),
+        Finding::OverlapUnchecked { a, b, steps } => format!(
+            r#""kind":"overlap_unchecked","a":{},"b":{},"steps":{}"#,
+            platform_json(level, *a),
+            platform_json(level, *b),
+            steps
+        ),
+        Finding::UnreachableCoin { position } => format!(
+            r#""kind":"unreachable_coin","x":{},"y":{}"#,
+            position.x, position.y
+        ),
+        Finding::GoalUnreachable => r#""kind":"goal_unreachable""#.to_owned(),
+        Finding::OutOfBounds { position } => format!(
+            r#""kind":"out_of_bounds","x":{},"y":{}"#,
+            position.x, position.y
+        ),
+        Finding::DeathOnly { platform } => format!(
+            r#""kind":"death_only","platform":{}"#,
+            platform_json(level, *platform)
+        ),
+        Finding::Softlock { platform } => format!(
+            r#""kind":"softlock","platform":{}"#,
+            platform_json(level, *platform)
+        ),
+    };
+    format!(r#"{{"severity":"{}",{}}}"#, severity, details)
+}
+
+const USAGE: &str =
+    "Usage: validate-level [--seed N] [--max-states N] [--generate SEED] [LEVEL_FILE]";
+
+enum Source {
+    Tower,
+    File(String),
+    Generated(u64),
+}
+
+fn parse_args() -> Result<(Options, Source), String> {
+    let mut options = Options::default();
+    let mut source = Source::Tower;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" | "--max-states" | "--generate" => {
+                let value = args
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .ok_or_else(|| format!("{} needs a number", arg))?;
+                match arg.as_str() {
+                    "--seed" => options.seed = value,
+                    "--max-states" => options.max_states = value as usize,
+                    _ => source = Source::Generated(value),
+                }
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ => source = Source::File(arg),
+        }
+    }
+    Ok((options, source))
+}
+
+fn main() -> ExitCode {
+    let (options, source) = match parse_args() {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    let level = match source {
+        Source::Tower => LevelDesc::parse(TOWER).unwrap(),
+        Source::File(path) => {
+            match std::fs::read_to_string(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|source| LevelDesc::parse(&source).map_err(|err| err.to_string()))
+            {
+                Ok(level) => level,
+                Err(err) => {
+                    eprintln!("{}: {}", path, err);
+                    return ExitCode::from(2);
+                }
+            }
+        }
+        Source::Generated(seed) => generate(seed, RANDOM_SECTIONS),
+    };
+
+    let report = validate(&level, &options);
+    let mut errors = 0;
+    let mut warnings = 0;
+    for finding in report.findings.iter() {
+        match finding.severity() {
+            Severity::Warning => warnings += 1,
+            Severity::Error => errors += 1,
+        }
+        println!("{}", finding_json(&level, finding));
+    }
+    println!(
+        r#"{{"summary":{{"states":{},"complete":{},"errors":{},"warnings":{}}}}}"#,
+        report.states, report.complete, errors, warnings
+    );
+    if errors > 0 || !report.complete {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
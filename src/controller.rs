@@ -0,0 +1,270 @@
+use crate::level::ParseError;
+use crate::player::FATAL_FALL;
+use crate::storage;
+
+/// Tuning overrides are read from this file, and written to it from the tuning panel.
+pub const TUNING_FILE: &str = "scavenger_drop_controller.txt";
+/// Seconds between checks of the tuning file for changes.
+const RELOAD_INTERVAL: f64 = 1.0;
+
+/// Names of the tuning values in files and the tuning panel, with how much one nudge
+/// in the panel changes them and the largest value they can be set to.
+pub const TUNING: [(&str, f32, f32); 11] = [
+    ("acceleration", 1.0 / 60.0, 1.0),
+    ("max_speed", 0.25, 16.0),
+    ("friction", 1.0 / 60.0, 1.0),
+    ("air_control", 0.05, 1.0),
+    ("wall_jump_speed", 0.25, 16.0),
+    ("slide_speed", 0.1, 16.0),
+    ("slide_deceleration", 1.0 / 60.0, 1.0),
+    ("fatal_fall", 0.25, 32.0),
+    ("landing_impact", 1.0 / 60.0, 32.0),
+    ("coyote_steps", 1.0, 60.0),
+    ("buffer_steps", 1.0, 60.0),
+];
+
+/// How the scavenger moves and how forgiving the controls are. Speeds are in pixels per
+/// step, changes of speed in pixels per step per step.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ControllerConfig {
+    /// Speed gained running, per step the direction is held.
+    pub acceleration: f32,
+    pub max_speed: f32,
+    /// Speed lost per step when no direction is held.
+    pub friction: f32,
+    /// Share of acceleration and friction left in mid-air.
+    pub air_control: f32,
+    /// Speed away from the wall when wall jumping.
+    pub wall_jump_speed: f32,
+    /// Fastest slide down a wall.
+    pub slide_speed: f32,
+    /// How quickly a wall slows a fall, or a jump, down to `slide_speed`.
+    pub slide_deceleration: f32,
+    /// Landing faster than this is fatal.
+    pub fatal_fall: f32,
+    /// Landing faster than this makes a sound and kicks up dust.
+    pub landing_impact: f32,
+    /// Steps after leaving a wall or ledge the scavenger can still jump off it.
+    pub coyote_steps: i32,
+    /// Steps a left or right press is remembered when it came too early for a wall jump.
+    pub buffer_steps: i32,
+}
+
+impl Default for ControllerConfig {
+    fn default() -> Self {
+        ControllerConfig {
+            acceleration: 8.0 / 60.0,
+            max_speed: 5.0,
+            friction: 16.0 / 60.0,
+            air_control: 0.5,
+            wall_jump_speed: 5.0,
+            slide_speed: 2.0,
+            slide_deceleration: 32.0 / 60.0,
+            fatal_fall: FATAL_FALL,
+            landing_impact: 6.0 / 60.0,
+            coyote_steps: 6,
+            buffer_steps: 6,
+        }
+    }
+}
+
+impl ControllerConfig {
+    /// `classic` is the default tuning, `assist` is slower and more forgiving.
+    pub fn preset(name: &str) -> Option<ControllerConfig> {
+        match name {
+            "classic" => Some(ControllerConfig::default()),
+            "assist" => Some(ControllerConfig {
+                acceleration: 10.0 / 60.0,
+                max_speed: 4.0,
+                air_control: 0.75,
+                slide_speed: 1.5,
+                fatal_fall: 10.0,
+                coyote_steps: 10,
+                buffer_steps: 10,
+                ..ControllerConfig::default()
+            }),
+            _ => None,
+        }
+    }
+
+    /// The value called `name` in `TUNING`.
+    pub fn get(&self, name: &str) -> Option<f32> {
+        Some(match name {
+            "acceleration" => self.acceleration,
+            "max_speed" => self.max_speed,
+            "friction" => self.friction,
+            "air_control" => self.air_control,
+            "wall_jump_speed" => self.wall_jump_speed,
+            "slide_speed" => self.slide_speed,
+            "slide_deceleration" => self.slide_deceleration,
+            "fatal_fall" => self.fatal_fall,
+            "landing_impact" => self.landing_impact,
+            "coyote_steps" => self.coyote_steps as f32,
+            "buffer_steps" => self.buffer_steps as f32,
+            _ => return None,
+        })
+    }
+
+    /// Sets the value called `name` in `TUNING`, false if there is none or `value` is not a
+    /// finite number. Values are kept between zero and their largest value in `TUNING` and
+    /// step counts are rounded.
+    pub fn set(&mut self, name: &str, value: f32) -> bool {
+        let Some(&(_, _, max)) = TUNING.iter().find(|(tuning, _, _)| *tuning == name) else {
+            return false;
+        };
+        if !value.is_finite() {
+            return false;
+        }
+        let value = value.clamp(0.0, max);
+        match name {
+            "acceleration" => self.acceleration = value,
+            "max_speed" => self.max_speed = value,
+            "friction" => self.friction = value,
+            "air_control" => self.air_control = value,
+            "wall_jump_speed" => self.wall_jump_speed = value,
+            "slide_speed" => self.slide_speed = value,
+            "slide_deceleration" => self.slide_deceleration = value,
+            "fatal_fall" => self.fatal_fall = value,
+            "landing_impact" => self.landing_impact = value,
+            "coyote_steps" => self.coyote_steps = value.round() as i32,
+            "buffer_steps" => self.buffer_steps = value.round() as i32,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Applies a tuning file on top of this config. Tuning files are line based, `#` starts
+    /// a comment:
+    ///
+    /// ```text
+    /// preset assist
+    /// max_speed 4.5
+    /// coyote_steps 8
+    /// ```
+    ///
+    /// `preset NAME` starts over from a preset, every other line sets one value of `TUNING`.
+    pub fn apply(&mut self, source: &str) -> Result<(), ParseError> {
+        for (index, line) in source.lines().enumerate() {
+            let error = |message: String| ParseError {
+                line: index + 1,
+                message,
+            };
+            let line = line.split('#').next().unwrap_or_default();
+            let mut words = line.split_whitespace();
+            let Some(name) = words.next() else {
+                continue;
+            };
+            let value = words
+                .next()
+                .ok_or_else(|| error(format!("missing value of '{}'", name)))?;
+            if let Some(word) = words.next() {
+                return Err(error(format!("unexpected '{}'", word)));
+            }
+            if name == "preset" {
+                *self = ControllerConfig::preset(value)
+                    .ok_or_else(|| error(format!("unknown preset '{}'", value)))?;
+                continue;
+            }
+            if self.get(name).is_none() {
+                return Err(error(format!("unknown tuning value '{}'", name)));
+            }
+            let number = value
+                .parse()
+                .ok()
+                .filter(|number: &f32| number.is_finite())
+                .ok_or_else(|| error(format!("invalid {} '{}'", name, value)))?;
+            self.set(name, number);
+        }
+        Ok(())
+    }
+
+    /// Every value as a tuning file.
+    pub fn to_file(&self) -> String {
+        TUNING
+            .iter()
+            .map(|(name, _, _)| format!("{} {}\n", name, self.get(name).unwrap_or_default()))
+            .collect()
+    }
+}
+
+/// The tuning file, checked for changes every `RELOAD_INTERVAL` while playing.
+#[derive(Default)]
+pub struct TuningFile {
+    contents: Option<String>,
+    checked: f64,
+}
+
+impl TuningFile {
+    pub fn load() -> TuningFile {
+        TuningFile {
+            contents: storage::read(TUNING_FILE),
+            checked: 0.0,
+        }
+    }
+
+    /// Rereads the file if it is time to, true if it changed. `now` is in seconds.
+    pub fn reload(&mut self, now: f64) -> bool {
+        if now - self.checked < RELOAD_INTERVAL {
+            return false;
+        }
+        self.checked = now;
+        let contents = storage::read(TUNING_FILE);
+        let changed = contents != self.contents;
+        self.contents = contents;
+        changed
+    }
+
+    /// `base`, usually the preset of the level, with the file applied on top.
+    pub fn config(&self, base: ControllerConfig) -> Result<ControllerConfig, ParseError> {
+        let mut config = base;
+        if let Some(contents) = &self.contents {
+            config.apply(contents)?;
+        }
+        Ok(config)
+    }
+
+    pub fn save(&mut self, config: &ControllerConfig) {
+        let contents = config.to_file();
+        storage::write(TUNING_FILE, &contents);
+        self.contents = Some(contents);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_starts_from_preset_and_sets_values() {
+        let mut config = ControllerConfig::default();
+        config
+            .apply("preset assist\nmax_speed 4.5 # faster\n\ncoyote_steps 7.6\n")
+            .unwrap();
+        assert_eq!(config.max_speed, 4.5);
+        assert_eq!(config.coyote_steps, 8);
+        assert_eq!(config.air_control, 0.75);
+    }
+
+    #[test]
+    fn apply_clamps_values() {
+        let mut config = ControllerConfig::default();
+        config.apply("max_speed 1e30\nfriction -3").unwrap();
+        assert_eq!(config.max_speed, 16.0);
+        assert_eq!(config.friction, 0.0);
+    }
+
+    #[test]
+    fn apply_rejects_bad_lines() {
+        for (source, line) in [
+            ("max_speed 4\nwarp_speed 9", 2),
+            ("max_speed NaN", 1),
+            ("max_speed inf", 1),
+            ("\nmax_speed", 2),
+            ("max_speed 4 5", 1),
+            ("preset turbo", 1),
+        ] {
+            let error = ControllerConfig::default().apply(source).unwrap_err();
+            assert_eq!(error.line, line, "{}", source);
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use crate::generate::*;
+use crate::storage;
+use std::fmt;
+
+/// Daily results are kept in this file, one line per day.
+const HISTORY_FILE: &str = "scavenger_drop_daily.txt";
+/// Mixed into the seeds of daily towers, so they are not the random towers with the number
+/// of the day as seed. It is no secret, the source is open.
+const SEED_SALT: u64 = 0x5ca7_d409_0da1_1e55;
+
+/// Days since 1970-01-01 in UTC.
+pub fn today() -> u64 {
+    (macroquad::miniquad::date::now() / 86400.0) as u64
+}
+
+/// The seed of the tower of `day`.
+pub fn seed(day: u64) -> u64 {
+    // SplitMix64
+    let mut z = (day ^ SEED_SALT).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// `day` as `YYYY-MM-DD`.
+pub fn date(day: u64) -> String {
+    // Civil from days, see https://howardhinnant.github.io/date_algorithms.html
+    let z = day + 719468;
+    let era = z / 146097;
+    let doe = z % 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + u64::from(m <= 2);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// Steps of the fixed 60Hz update as `MM:SS:mmm`.
+pub fn format_time(steps: u32) -> String {
+    let s = steps / 60;
+    let ms = ((steps % 60) as f32 / 60.0 * 1000.0) as u32;
+    format!("{:02}:{:02}:{:03}", s / 60, s % 60, ms)
+}
+
+/// Builds the tower everyone plays on `day`.
+pub fn builder(day: u64) -> TowerBuilder {
+    TowerBuilder::new(
+        format!("Daily Tower {}", date(day)),
+        seed(day),
+        RANDOM_SECTIONS,
+    )
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DailyResult {
+    pub day: u64,
+    /// Steps from leaving the soul-stone to reaching the ground.
+    pub time: u32,
+    pub diamonds: u32,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CodeError {
+    Malformed,
+    Checksum,
+    Future,
+    TooManyDiamonds { available: usize },
+}
+
+impl fmt::Display for CodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodeError::Malformed => write!(f, "not a result code"),
+            CodeError::Checksum => write!(f, "code was mistyped"),
+            CodeError::Future => write!(f, "tower of that day is not out yet"),
+            CodeError::TooManyDiamonds { available } => {
+                write!(f, "tower of that day only has {} diamonds", available)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CodeError {}
+
+/// Catches mistyped codes. Anyone can work it out, so it does not prove a result was played.
+fn checksum(day: u64, time: u32, diamonds: u32) -> u16 {
+    // FNV-1a
+    let text = format!("{}-{}-{}", day, time, diamonds);
+    let hash = text.bytes().fold(0x811c9dc5u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    });
+    (hash ^ (hash >> 16)) as u16
+}
+
+impl DailyResult {
+    /// Short text to share the result, like `SD-20379-3725-4-1a2b` for the day, time in steps,
+    /// diamonds and a checksum. Codes are not signed, anyone can make up one for any result.
+    pub fn code(&self) -> String {
+        format!(
+            "SD-{}-{}-{}-{:04x}",
+            self.day,
+            self.time,
+            self.diamonds,
+            checksum(self.day, self.time, self.diamonds)
+        )
+    }
+
+    /// Reads a result from a code made by `code`, rejecting mistyped ones.
+    pub fn from_code(code: &str) -> Result<DailyResult, CodeError> {
+        let mut parts = code.trim().split('-');
+        if parts.next() != Some("SD") {
+            return Err(CodeError::Malformed);
+        }
+        let day = parts.next().and_then(|part| part.parse().ok());
+        let time = parts.next().and_then(|part| part.parse().ok());
+        let diamonds = parts.next().and_then(|part| part.parse().ok());
+        let (Some(day), Some(time), Some(diamonds)) = (day, time, diamonds) else {
+            return Err(CodeError::Malformed);
+        };
+        let result = DailyResult {
+            day,
+            time,
+            diamonds,
+        };
+        let check = parts
+            .next()
+            .and_then(|part| u16::from_str_radix(part, 16).ok())
+            .ok_or(CodeError::Malformed)?;
+        if parts.next().is_some() {
+            return Err(CodeError::Malformed);
+        }
+        if check != checksum(result.day, result.time, result.diamonds) {
+            return Err(CodeError::Checksum);
+        }
+        Ok(result)
+    }
+
+    /// Checks that the tower of the day is out, before generating it to check the rest.
+    pub fn released(&self) -> Result<(), CodeError> {
+        if self.day > today() {
+            return Err(CodeError::Future);
+        }
+        Ok(())
+    }
+
+    /// Checks that the result fits the tower of its day, which has `available` diamonds. A
+    /// made-up result that fits cannot be told apart from a played one.
+    pub fn plausible(&self, available: usize) -> Result<(), CodeError> {
+        self.released()?;
+        if self.diamonds as usize > available {
+            return Err(CodeError::TooManyDiamonds { available });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DailyResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}  {}  {} diamonds",
+            date(self.day),
+            format_time(self.time),
+            self.diamonds
+        )
+    }
+}
+
+/// The scored attempt of a day, `result` is `None` if it has not reached the ground.
+pub struct Attempt {
+    pub day: u64,
+    pub result: Option<DailyResult>,
+}
+
+/// Daily attempts made on this machine.
+#[derive(Default)]
+pub struct History {
+    pub attempts: Vec<Attempt>,
+}
+
+impl History {
+    /// Reads the history, skipping lines that are not valid.
+    pub fn load() -> History {
+        storage::read(HISTORY_FILE)
+            .map(|source| History::parse(&source))
+            .unwrap_or_default()
+    }
+
+    fn parse(source: &str) -> History {
+        let attempts = source
+            .lines()
+            .filter_map(|line| match line.split_once(' ') {
+                Some((day, "failed")) => day.parse().ok().map(|day| Attempt { day, result: None }),
+                _ => DailyResult::from_code(line).ok().map(|result| Attempt {
+                    day: result.day,
+                    result: Some(result),
+                }),
+            })
+            .collect();
+        History { attempts }
+    }
+
+    fn save(&self) {
+        let lines: String = self
+            .attempts
+            .iter()
+            .map(|attempt| match attempt.result {
+                Some(result) => format!("{}\n", result.code()),
+                None => format!("{} failed\n", attempt.day),
+            })
+            .collect();
+        storage::write(HISTORY_FILE, &lines);
+    }
+
+    pub fn attempted(&self, day: u64) -> bool {
+        self.attempts.iter().any(|attempt| attempt.day == day)
+    }
+
+    /// Uses up the scored attempt of `day`. It counts as failed until `finish` is called, so
+    /// restarting the game does not give another try.
+    pub fn start(&mut self, day: u64) {
+        self.attempts.push(Attempt { day, result: None });
+        self.save();
+    }
+
+    pub fn finish(&mut self, result: DailyResult) {
+        if let Some(attempt) = self
+            .attempts
+            .iter_mut()
+            .find(|attempt| attempt.day == result.day)
+        {
+            attempt.result = Some(result);
+            self.save();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESULT: DailyResult = DailyResult {
+        day: 20379,
+        time: 3725,
+        diamonds: 4,
+    };
+
+    #[test]
+    fn code_round_trips() {
+        assert_eq!(DailyResult::from_code(&RESULT.code()), Ok(RESULT));
+        assert_eq!(
+            DailyResult::from_code(&format!(" {}\n", RESULT.code())),
+            Ok(RESULT)
+        );
+    }
+
+    #[test]
+    fn mistyped_codes_are_rejected() {
+        let code = RESULT.code().replace("-3725-", "-3724-");
+        assert_eq!(DailyResult::from_code(&code), Err(CodeError::Checksum));
+        for code in [
+            "",
+            "SD",
+            "XD-20379-3725-4-0000",
+            "SD-20379-3725-4",
+            "SD-1-2-3-zz",
+        ] {
+            assert_eq!(DailyResult::from_code(code), Err(CodeError::Malformed));
+        }
+        let code = format!("{}-1", RESULT.code());
+        assert_eq!(DailyResult::from_code(&code), Err(CodeError::Malformed));
+    }
+
+    #[test]
+    fn plausible_checks_day_and_diamonds() {
+        let available = 12;
+        let result = DailyResult {
+            diamonds: available as u32,
+            ..RESULT
+        };
+        assert_eq!(result.released(), Ok(()));
+        assert_eq!(result.plausible(available), Ok(()));
+        let greedy = DailyResult {
+            diamonds: available as u32 + 1,
+            ..result
+        };
+        assert_eq!(
+            greedy.plausible(available),
+            Err(CodeError::TooManyDiamonds { available })
+        );
+        let early = DailyResult {
+            day: today() + 1,
+            ..result
+        };
+        assert_eq!(early.released(), Err(CodeError::Future));
+        assert_eq!(early.plausible(available), Err(CodeError::Future));
+    }
+
+    #[test]
+    fn seeds_differ_from_days() {
+        assert_ne!(seed(20379), 20379);
+        assert_ne!(seed(20379), seed(20380));
+    }
+}
@@ -0,0 +1,242 @@
+use crate::level::BODY;
+use crate::physics::*;
+use macroquad::prelude::*;
+
+/// Steps a turret shot flies before it is gone.
+const SHOT_STEPS: i32 = 300;
+/// Steps a bat swoops before flying back.
+const SWOOP_STEPS: i32 = 90;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Behavior {
+    /// Walks along its floor with `speed`, turning at walls and ledges.
+    Patrol { speed: f32 },
+    /// Hangs still until the scavenger comes within `range`, then swoops at it.
+    Bat { range: f32, speed: f32 },
+    /// Fires a shot flying with `velocity` every `interval` steps.
+    Turret { velocity: Vec2, interval: i32 },
+}
+
+/// What touching an enemy or its shots does to the scavenger.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Contact {
+    Kill,
+    /// Throws the scavenger away from the enemy.
+    KnockBack,
+}
+
+/// Enemy as given in the level file.
+#[derive(Copy, Clone, Debug)]
+pub struct EnemyDesc {
+    pub position: Vec2,
+    pub behavior: Behavior,
+    pub contact: Contact,
+}
+
+impl EnemyDesc {
+    pub fn build(&self, world: &mut World) -> Enemy {
+        let mut enemy = Enemy {
+            actor: world.add_actor(self.position, vec2(32.0, 32.0), BODY),
+            behavior: self.behavior,
+            contact: self.contact,
+            initial_position: self.position,
+            velocity: Vec2::ZERO,
+            timer: 0,
+            shots: Vec::new(),
+        };
+        enemy.reset(world);
+        enemy
+    }
+}
+
+pub struct Shot {
+    pub actor: Actor,
+    pub velocity: Vec2,
+    pub timer: i32,
+}
+
+pub struct Enemy {
+    pub actor: Actor,
+    pub behavior: Behavior,
+    pub contact: Contact,
+    pub initial_position: Vec2,
+    pub velocity: Vec2,
+    /// Steps until a turret fires, or a bat has been swooping.
+    pub timer: i32,
+    /// Shots of a turret that are still flying.
+    pub shots: Vec<Shot>,
+}
+
+impl Enemy {
+    pub fn reset(&mut self, world: &mut World) {
+        world.set_actor_pos(self.actor, self.initial_position);
+        self.velocity = match self.behavior {
+            Behavior::Patrol { speed } => vec2(speed, 0.0),
+            Behavior::Bat { .. } | Behavior::Turret { .. } => Vec2::ZERO,
+        };
+        self.timer = match self.behavior {
+            Behavior::Turret { interval, .. } => interval,
+            Behavior::Patrol { .. } | Behavior::Bat { .. } => 0,
+        };
+        for shot in self.shots.drain(..) {
+            world.remove_actor(shot.actor);
+        }
+    }
+
+    /// Moves the enemy one step, `target` is where the scavenger is.
+    pub fn step(&mut self, world: &mut World, target: Vec2) {
+        let position = world.actor_pos(self.actor);
+        match self.behavior {
+            Behavior::Patrol { .. } => {
+                let (wall, _) = world.move_h(self.actor, self.velocity.x);
+                // Probe just below the front edge for the floor continuing
+                let front = if self.velocity.x > 0.0 { 32.0 } else { -1.0 };
+                let ahead = world.actor_pos(self.actor) + vec2(front, 32.0);
+                if wall.is_some() || world.collide_solids(ahead, vec2(1.0, 1.0)).is_none() {
+                    self.velocity.x = -self.velocity.x;
+                }
+            }
+            Behavior::Bat { range, speed } => {
+                if self.velocity == Vec2::ZERO {
+                    // Hanging, or back home after a swoop
+                    if position.distance(target) < range {
+                        self.velocity = (target - position).normalize_or_zero() * speed;
+                        self.timer = 0;
+                    }
+                } else if self.timer < SWOOP_STEPS {
+                    self.timer += 1;
+                    let (wall_h, _) = world.move_h(self.actor, self.velocity.x);
+                    let (wall_v, _) = world.move_v(self.actor, self.velocity.y);
+                    if wall_h.is_some() || wall_v.is_some() {
+                        self.timer = SWOOP_STEPS;
+                    }
+                } else {
+                    let home = self.initial_position - position;
+                    if home.length() <= speed / 2.0 {
+                        world.set_actor_pos(self.actor, self.initial_position);
+                        self.velocity = Vec2::ZERO;
+                    } else {
+                        // Flies back through anything in the way
+                        world.set_actor_pos(self.actor, position + home.normalize() * speed / 2.0);
+                    }
+                }
+            }
+            Behavior::Turret { velocity, interval } => {
+                self.timer -= 1;
+                if self.timer <= 0 {
+                    self.timer = interval;
+                    self.shots.push(Shot {
+                        actor: world.add_actor(position + vec2(8.0, 8.0), vec2(16.0, 16.0), 0),
+                        velocity,
+                        timer: SHOT_STEPS,
+                    });
+                }
+            }
+        }
+        self.shots.retain_mut(|shot| {
+            shot.timer -= 1;
+            let position = world.actor_pos(shot.actor) + shot.velocity;
+            world.set_actor_pos(shot.actor, position);
+            let hit = world.collide_solids(position, vec2(16.0, 16.0)).is_some();
+            if hit || shot.timer <= 0 {
+                world.remove_actor(shot.actor);
+            }
+            !hit && shot.timer > 0
+        });
+    }
+
+    /// Whether the enemy or one of its shots touches `rect`.
+    pub fn touches(&self, world: &World, rect: Rect) -> bool {
+        std::iter::once(self.actor)
+            .chain(self.shots.iter().map(|shot| shot.actor))
+            .any(|actor| world.actor_collider(actor).as_rect().overlaps(&rect))
+    }
+}
+
+/// Steps before a rock drops that dust trickles down as a warning.
+pub const WARNING_STEPS: i32 = 60;
+/// Steps a rock falls before it is gone, even without hitting anything.
+const ROCK_STEPS: i32 = 600;
+
+/// Drops a rock from `position` every `interval` steps, the first after `offset` more.
+#[derive(Copy, Clone, Debug)]
+pub struct RockfallDesc {
+    pub position: Vec2,
+    pub interval: i32,
+    pub offset: i32,
+}
+
+impl RockfallDesc {
+    pub fn build(&self) -> Rockfall {
+        Rockfall {
+            desc: *self,
+            timer: self.interval + self.offset,
+            rocks: Vec::new(),
+        }
+    }
+}
+
+pub struct Rock {
+    pub actor: Actor,
+    pub dy: f32,
+    pub timer: i32,
+}
+
+pub struct Rockfall {
+    pub desc: RockfallDesc,
+    /// Steps until the next rock drops.
+    pub timer: i32,
+    pub rocks: Vec<Rock>,
+}
+
+/// What a rockfall did during a step, for dust and sounds.
+#[derive(Default)]
+pub struct RockfallStep {
+    pub warning: bool,
+    /// Where rocks hit something and broke.
+    pub broken: Vec<Vec2>,
+}
+
+impl Rockfall {
+    pub fn reset(&mut self, world: &mut World) {
+        self.timer = self.desc.interval + self.desc.offset;
+        for rock in self.rocks.drain(..) {
+            world.remove_actor(rock.actor);
+        }
+    }
+
+    pub fn step(&mut self, world: &mut World) -> RockfallStep {
+        let mut step = RockfallStep::default();
+        self.timer -= 1;
+        if self.timer <= 0 {
+            self.timer = self.desc.interval;
+            self.rocks.push(Rock {
+                actor: world.add_actor(self.desc.position - vec2(8.0, 0.0), vec2(16.0, 16.0), 0),
+                dy: 0.0,
+                timer: ROCK_STEPS,
+            });
+        }
+        step.warning = self.timer <= WARNING_STEPS;
+        self.rocks.retain_mut(|rock| {
+            rock.dy += GRAVITY.y;
+            rock.timer -= 1;
+            let (hit, _) = world.move_v(rock.actor, rock.dy);
+            if hit.is_some() {
+                step.broken
+                    .push(world.actor_pos(rock.actor) + vec2(8.0, 16.0));
+            }
+            if hit.is_some() || rock.timer <= 0 {
+                world.remove_actor(rock.actor);
+                return false;
+            }
+            true
+        });
+        step
+    }
+
+    pub fn touches(&self, world: &World, rect: Rect) -> bool {
+        self.rocks
+            .iter()
+            .any(|rock| world.actor_collider(rock.actor).as_rect().overlaps(&rect))
+    }
+}
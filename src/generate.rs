@@ -0,0 +1,680 @@
+use crate::level::*;
+use crate::motion::*;
+use crate::player::*;
+use crate::validate::*;
+use macroquad::prelude::*;
+
+/// Generated towers span from `-HALF_WIDTH` to `HALF_WIDTH`, walls are outside of that.
+pub const HALF_WIDTH: f32 = 320.0;
+/// Sections in a random tower of the game, endless towers keep generating them.
+pub const RANDOM_SECTIONS: usize = 12;
+/// Random sections tried before falling back to plain drop ledges.
+const SECTION_TRIES: usize = 8;
+/// Search limit when checking a single section.
+const SECTION_MAX_STATES: usize = 150_000;
+/// Sections before a new one that are checked along with it.
+const CHECKED_BEFORE: usize = 1;
+/// Search limit when checking a whole tower.
+const TOWER_MAX_STATES: usize = 2_000_000;
+/// Random towers tried for a seed before settling for plain drop ledges.
+const TOWER_ATTEMPTS: u32 = 3;
+/// Walls are a single tile wide, too narrow for diamonds to end up on them.
+const WALL: f32 = 16.0;
+
+/// Small PCG generator, so towers do not depend on the global `rand` state.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Rng(seed.wrapping_add(1442695040888963407));
+        rng.next_u32();
+        rng
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.0;
+        self.0 = old
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+
+    pub fn range(&mut self, low: f32, high: f32) -> f32 {
+        low + (high - low) * (self.next_u32() as f32 / (u32::MAX as f32 + 1.0))
+    }
+
+    /// Random multiple of 16 in `low..=high`.
+    pub fn tiles(&mut self, low: f32, high: f32) -> f32 {
+        let low = (low / 16.0).ceil() * 16.0;
+        let tiles = ((high - low) / 16.0).floor() as u32 + 1;
+        low + (self.next_u32() % tiles) as f32 * 16.0
+    }
+
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.range(0.0, 1.0) < probability
+    }
+
+    pub fn sign(&mut self) -> f32 {
+        if self.chance(0.5) {
+            1.0
+        } else {
+            -1.0
+        }
+    }
+}
+
+/// Platforms between the ledge a section is entered from and the one it is left on.
+struct Section {
+    platforms: Vec<PlatformDesc>,
+    /// Index of the ledge the next section starts from.
+    exit: usize,
+}
+
+impl Section {
+    fn new() -> Self {
+        Section {
+            platforms: Vec::new(),
+            exit: 0,
+        }
+    }
+
+    fn add(&mut self, position: Vec2, size: Vec2, flags: u16) -> &mut PlatformDesc {
+        self.platforms
+            .push(PlatformDesc::new(position, size, flags));
+        self.platforms.last_mut().unwrap()
+    }
+
+    fn exit(&mut self, position: Vec2, size: Vec2) {
+        self.exit = self.platforms.len();
+        self.add(position, size, NO_SLIDE);
+    }
+
+    fn bottom(&self) -> f32 {
+        self.platforms
+            .iter()
+            .map(|platform| platform.position.y + platform.size.y)
+            .fold(f32::MIN, f32::max)
+    }
+}
+
+fn clamp_x(x: f32, width: f32) -> f32 {
+    x.clamp(-HALF_WIDTH, HALF_WIDTH - width)
+}
+
+/// Direction to walk off `entry` with enough room for `space` next to it.
+fn side(rng: &mut Rng, entry: Rect, space: f32) -> f32 {
+    let fits_left = entry.left() - space >= -HALF_WIDTH;
+    let fits_right = entry.right() + space <= HALF_WIDTH;
+    match (fits_left, fits_right) {
+        (true, true) => rng.sign(),
+        (true, false) => -1.0,
+        _ => 1.0,
+    }
+}
+
+/// A few ledges, each one a survivable drop below the previous one.
+fn drop_ledges(rng: &mut Rng, entry: Rect) -> Section {
+    let mut section = Section::new();
+    let mut ledge = entry;
+    let count = 2 + rng.next_u32() % 3;
+    for index in 0..count {
+        let width = rng.tiles(96.0, 192.0);
+        let direction = side(rng, ledge, width);
+        let x = if direction > 0.0 {
+            ledge.right() + rng.tiles(-32.0, 48.0)
+        } else {
+            ledge.left() - width - rng.tiles(-32.0, 48.0)
+        };
+        let position = vec2(
+            clamp_x(x, width),
+            ledge.y + rng.tiles(96.0, max_safe_drop() * 0.85),
+        );
+        let size = vec2(width, 32.0);
+        if index + 1 == count {
+            section.exit(position, size);
+        } else {
+            section.add(position, size, NO_SLIDE);
+        }
+        ledge = Rect::new(position.x, position.y, size.x, size.y);
+    }
+    section
+}
+
+/// A wall next to the entry that has to be slid down to survive the drop.
+fn slide_wall(rng: &mut Rng, entry: Rect) -> Section {
+    let mut section = Section::new();
+    let direction = side(rng, entry, 160.0);
+    let drop = rng.tiles(max_safe_drop() * 1.3, max_safe_drop() * 2.5);
+    let wall_x = if direction > 0.0 {
+        entry.right() + 32.0
+    } else {
+        entry.left() - 32.0 - WALL
+    };
+    // Ends above the exit, so the exit can be walked off below it
+    section.add(vec2(wall_x, entry.y - 64.0), vec2(WALL, drop + 16.0), 0);
+    let width = rng.tiles(96.0, 160.0);
+    let exit_x = if direction > 0.0 {
+        wall_x - width
+    } else {
+        wall_x + WALL
+    };
+    section.exit(vec2(exit_x, entry.y + drop), vec2(width, 32.0));
+    section
+}
+
+/// A tempting wall that cannot be slid down, the safe way is on the other side.
+fn no_slide_wall(rng: &mut Rng, entry: Rect) -> Section {
+    let mut section = Section::new();
+    let direction = side(rng, entry, 160.0);
+    let exit_y = entry.y + rng.tiles(96.0, max_safe_drop() * 0.85);
+    let wall_x = if direction > 0.0 {
+        entry.right() + 32.0
+    } else {
+        entry.left() - 32.0 - WALL
+    };
+    section.add(
+        vec2(wall_x, entry.y - 64.0),
+        vec2(WALL, exit_y - entry.y + 96.0),
+        NO_SLIDE,
+    );
+    let width = rng.tiles(96.0, 160.0);
+    let exit_x = if direction > 0.0 {
+        entry.left() - width + rng.tiles(0.0, 32.0)
+    } else {
+        entry.right() - rng.tiles(0.0, 32.0)
+    };
+    section.exit(vec2(clamp_x(exit_x, width), exit_y), vec2(width, 32.0));
+    section
+}
+
+/// Staggered walls on both sides of a shaft, jumping between them breaks the fall.
+fn wall_jump_shaft(rng: &mut Rng, entry: Rect) -> Section {
+    let mut section = Section::new();
+    let direction = side(rng, entry, 224.0);
+    let width = rng.tiles(128.0, 224.0);
+    let left = if direction > 0.0 {
+        entry.right() + 32.0
+    } else {
+        entry.left() - 32.0 - WALL - width
+    };
+    let left = clamp_x(left, WALL + width);
+    let right = left + width;
+    let walls = 2 + rng.next_u32() % 3;
+    let mut y = entry.y - 32.0;
+    let mut on_left = direction < 0.0;
+    for _ in 0..walls {
+        let height = rng.tiles(160.0, 288.0);
+        let x = if on_left { left } else { right };
+        section.add(vec2(x, y), vec2(WALL, height), 0);
+        y += height - rng.tiles(0.0, 64.0);
+        on_left = !on_left;
+    }
+    section.exit(
+        vec2(left + WALL, y + rng.tiles(64.0, max_safe_drop() * 0.5)),
+        vec2(width - WALL, 32.0),
+    );
+    section
+}
+
+/// A platform that carries the scavenger across a fatal drop.
+fn ferry(rng: &mut Rng, entry: Rect) -> Section {
+    let mut section = Section::new();
+    let direction = side(rng, entry, 288.0);
+    let distance = rng.tiles(192.0, 352.0);
+    let start_x = if direction > 0.0 {
+        entry.right()
+    } else {
+        entry.left() - 96.0
+    };
+    // Keep the scavenger from being carried into the outer walls
+    let end_x = (start_x + direction * distance).clamp(-HALF_WIDTH + 32.0, HALF_WIDTH - 128.0);
+    let steps = (distance * rng.range(0.6, 1.2)) as i32;
+    let pause = rng.tiles(60.0, 180.0) as i32;
+    let ferry = section.add(vec2(start_x, entry.y), vec2(96.0, 32.0), NO_SLIDE);
+    ferry.moves = vec![
+        PlatformMove::Pause { steps: pause },
+        PlatformMove::ToTarget {
+            target: vec2(end_x, entry.y),
+            steps,
+            easing: Easing::Linear,
+        },
+        PlatformMove::Pause { steps: pause },
+        PlatformMove::ToTarget {
+            target: vec2(start_x, entry.y),
+            steps,
+            easing: Easing::Linear,
+        },
+    ];
+    let width = rng.tiles(96.0, 160.0);
+    section.exit(
+        vec2(
+            clamp_x(end_x + 48.0 - width / 2.0, width),
+            entry.y + rng.tiles(128.0, max_safe_drop() * 0.85),
+        ),
+        vec2(width, 32.0),
+    );
+    section
+}
+
+/// A shaft below the edge of the entry to slide down, with deadly pistons moving along its walls.
+fn deadly_gauntlet(rng: &mut Rng, entry: Rect) -> Section {
+    let mut section = Section::new();
+    let direction = side(rng, entry, 320.0);
+    let width = 256.0;
+    let left = if direction > 0.0 {
+        entry.right() - 16.0 - WALL
+    } else {
+        entry.left() + 16.0 - width
+    };
+    let left = clamp_x(left, WALL + width);
+    let right = left + width;
+    let top = entry.y + 64.0;
+    let pistons = 1 + rng.next_u32() % 3;
+    let height = 320.0 + pistons as f32 * 288.0;
+    section.add(vec2(left, top), vec2(WALL, height), 0);
+    section.add(vec2(right, top), vec2(WALL, height), 0);
+    let steps = rng.tiles(128.0, 224.0) as i32;
+    for index in 0..pistons {
+        let y = top + 288.0 + index as f32 * 288.0;
+        let x = if index % 2 == 0 {
+            left + WALL
+        } else {
+            right - 64.0
+        };
+        let travel = rng.tiles(96.0, 224.0);
+        let piston = section.add(vec2(x, y), vec2(64.0, 16.0), DEADLY);
+        piston.moves = vec![
+            PlatformMove::ToTarget {
+                target: vec2(x, y - travel),
+                steps,
+                easing: Easing::Linear,
+            },
+            PlatformMove::ToTarget {
+                target: vec2(x, y),
+                steps,
+                easing: Easing::Linear,
+            },
+        ];
+    }
+    // Below the end of the shaft, so the exit can be walked off on both sides
+    section.exit(
+        vec2(left + WALL, top + height + 64.0),
+        vec2(width - WALL, 32.0),
+    );
+    section
+}
+
+/// A plain ledge below the entry, used when none of the templates fit. Only sections that can
+/// be continued with it are accepted, so the tower can always go on.
+fn fallback(entry: Rect) -> Section {
+    let mut section = Section::new();
+    let x = if entry.right() + 96.0 <= HALF_WIDTH {
+        entry.right()
+    } else {
+        entry.left() - 96.0
+    };
+    section.exit(vec2(x, entry.y + 128.0), vec2(96.0, 32.0));
+    section
+}
+
+const TEMPLATES: [fn(&mut Rng, Rect) -> Section; 6] = [
+    drop_ledges,
+    slide_wall,
+    no_slide_wall,
+    wall_jump_shaft,
+    ferry,
+    deadly_gauntlet,
+];
+
+/// Diamonds on roughly 30% of the ledges of `section`, like `scatter_coins` places them in
+/// hand-built levels. Moving platforms would carry them off, so they are left out.
+fn coins(rng: &mut Rng, section: &Section) -> Vec<Vec2> {
+    let mut coins = Vec::new();
+    for platform in section.platforms.iter() {
+        let ledge = platform.flags & DEADLY == 0
+            && platform.moves.is_empty()
+            && platform.size.x >= 32.0
+            && platform.size.x >= platform.size.y;
+        if ledge && rng.chance(0.3) {
+            let x = platform.position.x + rng.range(0.0, platform.size.x - 32.0);
+            coins.push(vec2(x, platform.position.y - 32.0));
+        }
+    }
+    coins
+}
+
+/// Starts checking `section` along with the `previous` sections, from the ledge the first of
+/// them was entered from down to a plain ledge below the exit of `section`. Every ledge and wall
+/// top on the way has to lead there without dying and missing it has to be fatal rather than
+/// endless. This weeds out bad sections early, routes from further up are only covered when
+/// `TowerBuilder` checks the finished tower. `None` if the section does not fit.
+fn check(
+    previous: &[PlatformDesc],
+    entry: &PlatformDesc,
+    section: &Section,
+    coins: &[Vec2],
+) -> Option<Validation> {
+    // Nothing may hang below the exit, the next section is only checked along with this one
+    let exit = &section.platforms[section.exit];
+    if section.bottom() > exit.position.y + exit.size.y
+        || section.platforms.iter().any(|platform| {
+            platform.position.x < -HALF_WIDTH
+                || platform.position.x + platform.size.x > HALF_WIDTH
+                || platform.position.y < entry.position.y - 64.0
+        })
+    {
+        return None;
+    }
+    // The ledge the generator falls back to, so the tower can always go on from the exit
+    let mut goal = fallback(exit.rect()).platforms.remove(0);
+    goal.flags |= GROUND_LEVEL;
+    let first = previous.first().unwrap_or(entry);
+    let bottom = goal.position.y + goal.size.y + max_safe_drop() * 2.0;
+    let mut level = LevelDesc {
+        start: first.position + vec2(first.size.x / 2.0 - 16.0, -32.0),
+        coins: Some(coins.to_vec()),
+        ..Default::default()
+    };
+    level.platforms.extend_from_slice(previous);
+    level.platforms.push(entry.clone());
+    level.platforms.extend_from_slice(&section.platforms);
+    level.platforms.push(goal);
+    level.platforms.extend(walls(first.position.y - 64.0, bottom));
+    level.platforms.push(PlatformDesc::new(
+        vec2(-HALF_WIDTH, bottom),
+        vec2(HALF_WIDTH * 2.0, 16.0),
+        DEADLY,
+    ));
+    // Standing on any ledge or wall top, however it was reached, has to lead on as well
+    let starts = level
+        .platforms
+        .iter()
+        .filter(|platform| platform.flags & DEADLY == 0)
+        .map(|platform| platform.position + vec2(platform.size.x / 2.0 - 16.0, -32.0))
+        .filter(|&start| {
+            let rect = Rect::new(start.x, start.y, 32.0, 32.0);
+            rect.x >= -HALF_WIDTH
+                && rect.right() <= HALF_WIDTH
+                && !level.platforms.iter().any(|platform| {
+                    rect.intersect(platform.rect())
+                        .is_some_and(|overlap| overlap.w > 0.0 && overlap.h > 0.0)
+                })
+        })
+        .collect();
+    Some(Validation::new(
+        &level,
+        &Options {
+            max_states: SECTION_MAX_STATES,
+            starts,
+            ..Default::default()
+        },
+    ))
+}
+
+/// Which of `coins` can be reached, `None` if the section did not pass its check.
+fn reachable(report: Report, coins: &[Vec2]) -> Option<Vec<Vec2>> {
+    let mut reachable = coins.to_vec();
+    for finding in report.findings {
+        match finding {
+            Finding::UnreachableCoin { position } => {
+                reachable.retain(|coin| coin.distance(position) > 1.0);
+            }
+            // Unlike in hand-built levels, overlapping platforms are never intended here
+            _ => return None,
+        }
+    }
+    report.complete.then_some(reachable)
+}
+
+/// The outer walls between `top` and `bottom`.
+fn walls(top: f32, bottom: f32) -> [PlatformDesc; 2] {
+    let top = (top / 16.0).floor() * 16.0;
+    let size = vec2(WALL, ((bottom - top) / 16.0).ceil() * 16.0);
+    [
+        PlatformDesc::new(vec2(-HALF_WIDTH - WALL, top), size, NO_SLIDE),
+        PlatformDesc::new(vec2(HALF_WIDTH, top), size, NO_SLIDE),
+    ]
+}
+
+/// Platforms of a generated section including the outer walls along it, and its diamonds.
+pub struct SectionDesc {
+    pub platforms: Vec<PlatformDesc>,
+    pub coins: Vec<Vec2>,
+}
+
+/// A section that is still being checked.
+struct Candidate {
+    section: Section,
+    coins: Vec<Vec2>,
+    validation: Validation,
+}
+
+/// Builds a tower section by section. The same seed always results in the same tower. Endless
+/// towers never end, so they only get the checks of single sections.
+pub struct TowerGenerator {
+    rng: Rng,
+    /// Only plain ledges, which always lead on.
+    plain: bool,
+    entry: PlatformDesc,
+    /// Entries and platforms of the last sections, without their exits. New sections are
+    /// checked starting from the first of them.
+    previous: Vec<Vec<PlatformDesc>>,
+    candidate: Option<Box<Candidate>>,
+    /// Sections tried for the next one so far.
+    tries: usize,
+}
+
+impl TowerGenerator {
+    pub fn new(seed: u64) -> Self {
+        TowerGenerator {
+            rng: Rng::new(seed),
+            plain: false,
+            entry: PlatformDesc::new(vec2(-48.0, 32.0), vec2(96.0, 32.0), NO_SLIDE),
+            previous: Vec::new(),
+            candidate: None,
+            tries: 0,
+        }
+    }
+
+    /// The spot to start from and the platforms around it.
+    pub fn start(&self) -> (Vec2, Vec<PlatformDesc>) {
+        let mut platforms = vec![self.entry.clone()];
+        platforms.extend(walls(-256.0, self.entry.position.y + self.entry.size.y));
+        (self.entry.position + vec2(32.0, -32.0), platforms)
+    }
+
+    /// The next section, checked along with the ones before it.
+    pub fn next_section(&mut self) -> SectionDesc {
+        loop {
+            if let Some(section) = self.work(usize::MAX) {
+                return section;
+            }
+        }
+    }
+
+    /// Works on the next section for at most `budget` search states and returns it once it is
+    /// done, so generating can be spread over frames.
+    pub fn work(&mut self, budget: usize) -> Option<SectionDesc> {
+        if self.candidate.is_none() {
+            if self.tries == SECTION_TRIES || self.plain {
+                return Some(self.accept(fallback(self.entry.rect()), Vec::new()));
+            }
+            self.tries += 1;
+            let template = TEMPLATES[self.rng.next_u32() as usize % TEMPLATES.len()];
+            let section = template(&mut self.rng, self.entry.rect());
+            let coins = coins(&mut self.rng, &section);
+            let validation = check(&self.previous.concat(), &self.entry, &section, &coins)?;
+            self.candidate = Some(Box::new(Candidate {
+                section,
+                coins,
+                validation,
+            }));
+        }
+        if !self.candidate.as_mut()?.validation.explore(budget) {
+            return None;
+        }
+        let Candidate {
+            section,
+            coins,
+            validation,
+        } = *self.candidate.take()?;
+        let coins = reachable(validation.finish(), &coins)?;
+        Some(self.accept(section, coins))
+    }
+
+    fn accept(&mut self, section: Section, coins: Vec<Vec2>) -> SectionDesc {
+        self.tries = 0;
+        let top = self.entry.position.y + self.entry.size.y;
+        let Section {
+            mut platforms,
+            exit,
+        } = section;
+        let exit = platforms.remove(exit);
+        self.previous.push(
+            std::iter::once(std::mem::replace(&mut self.entry, exit.clone()))
+                .chain(platforms.iter().cloned())
+                .collect(),
+        );
+        if self.previous.len() > CHECKED_BEFORE {
+            self.previous.remove(0);
+        }
+        platforms.push(exit);
+        platforms.extend(walls(top, self.entry.position.y + self.entry.size.y));
+        SectionDesc { platforms, coins }
+    }
+
+    /// Top of the ledge the last generated section ends on.
+    pub fn depth(&self) -> f32 {
+        self.entry.position.y
+    }
+
+    /// Ground below the last generated section, level with the ledge it was checked against.
+    pub fn ground(&self) -> Vec<PlatformDesc> {
+        let y = self.entry.position.y + 128.0;
+        let mut platforms = vec![PlatformDesc::new(
+            vec2(-HALF_WIDTH - WALL, y),
+            vec2(HALF_WIDTH * 2.0 + WALL * 2.0, 32.0),
+            GROUND_LEVEL | NO_SLIDE,
+        )];
+        platforms.extend(walls(self.entry.position.y + self.entry.size.y, y));
+        platforms
+    }
+}
+
+/// Builds a tower with a fixed number of sections and ground below them, then checks it as a
+/// whole from the top. Towers that do not pass are thrown away for another attempt, the last
+/// one only has plain ledges. Work is done a few search states at a time, so it can be spread
+/// over frames.
+pub struct TowerBuilder {
+    name: String,
+    seed: u64,
+    sections: usize,
+    attempt: u32,
+    generator: TowerGenerator,
+    level: LevelDesc,
+    /// Sections of the current attempt so far.
+    built: usize,
+    coins: Vec<Vec2>,
+    /// Checks the finished tower, once every section is there.
+    validation: Option<Box<Validation>>,
+}
+
+impl TowerBuilder {
+    pub fn new(name: String, seed: u64, sections: usize) -> Self {
+        let mut builder = TowerBuilder {
+            name,
+            seed,
+            sections,
+            attempt: 0,
+            generator: TowerGenerator::new(seed),
+            level: LevelDesc::default(),
+            built: 0,
+            coins: Vec::new(),
+            validation: None,
+        };
+        builder.restart();
+        builder
+    }
+
+    /// Starts the tower over, attempts after the first get seeds of their own.
+    fn restart(&mut self) {
+        let seed = self.seed ^ (self.attempt as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        self.generator = TowerGenerator::new(seed);
+        self.generator.plain = self.attempt + 1 == TOWER_ATTEMPTS;
+        let (start, platforms) = self.generator.start();
+        self.level = LevelDesc {
+            name: self.name.clone(),
+            start,
+            platforms,
+            ..Default::default()
+        };
+        self.built = 0;
+        self.coins.clear();
+        self.validation = None;
+    }
+
+    /// How far the current attempt is, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        let done = self.built + self.validation.is_some() as usize;
+        done as f32 / (self.sections + 1) as f32
+    }
+
+    /// Works on the tower for at most `budget` search states and returns it once it is done.
+    pub fn work(&mut self, budget: usize) -> Option<LevelDesc> {
+        if self.built < self.sections {
+            let section = self.generator.work(budget)?;
+            self.level.platforms.extend(section.platforms);
+            self.coins.extend(section.coins);
+            self.built += 1;
+            return None;
+        }
+        let validation = match &mut self.validation {
+            Some(validation) => validation,
+            None => {
+                self.level.platforms.extend(self.generator.ground());
+                self.level.coins = Some(std::mem::take(&mut self.coins));
+                if self.generator.plain {
+                    // Plain ledges are a survivable drop apart and lead on by themselves
+                    return Some(std::mem::take(&mut self.level));
+                }
+                let options = Options {
+                    max_states: TOWER_MAX_STATES,
+                    ..Default::default()
+                };
+                self.validation
+                    .insert(Box::new(Validation::new(&self.level, &options)))
+            }
+        };
+        if !validation.explore(budget) {
+            return None;
+        }
+        let report = self.validation.take()?.finish();
+        let coins = self.level.coins.as_deref().unwrap_or_default();
+        match reachable(report, coins) {
+            Some(coins) => {
+                self.level.coins = Some(coins);
+                Some(std::mem::take(&mut self.level))
+            }
+            None => {
+                self.attempt += 1;
+                self.restart();
+                None
+            }
+        }
+    }
+
+    /// Works on the tower until it is done, which takes a few seconds.
+    pub fn build(mut self) -> LevelDesc {
+        loop {
+            if let Some(level) = self.work(usize::MAX) {
+                return level;
+            }
+        }
+    }
+}
+
+/// A random tower with `sections` sections and ground below them.
+pub fn generate(seed: u64, sections: usize) -> LevelDesc {
+    TowerBuilder::new(format!("Random Tower {}", seed), seed, sections).build()
+}
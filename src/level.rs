@@ -0,0 +1,1942 @@
+use crate::controller::ControllerConfig;
+use crate::enemy::*;
+use crate::motion::*;
+use crate::physics::*;
+use crate::player::{Abilities, Ability, DEFAULT_HEALTH};
+use macroquad::prelude::*;
+use macroquad::rand::*;
+use std::fmt;
+
+pub const NO_SLIDE: u16 = 1;
+pub const GROUND_LEVEL: u16 = 2;
+pub const DEADLY: u16 = 4;
+pub const COIN: u16 = 8;
+pub const NOT_TAKEN: u16 = 16;
+/// Switches are actors, keys can be taken like coins.
+pub const KEY: u16 = 32;
+pub const LEVER: u16 = 64;
+pub const PLATE: u16 = 128;
+/// The scavenger and enemies, the only actors that stand on and get in the way of platforms.
+pub const BODY: u16 = 256;
+
+/// The hand-built tower the game starts with.
+pub const TOWER: &str = include_str!("../assets/levels/tower.lvl");
+/// The hand-built towers in the order they are meant to be played.
+pub const LEVELS: [&str; 3] = [
+    TOWER,
+    include_str!("../assets/levels/chimney.lvl"),
+    include_str!("../assets/levels/pit.lvl"),
+];
+
+const FLAG_NAMES: [(&str, u16); 3] = [
+    ("no_slide", NO_SLIDE),
+    ("ground", GROUND_LEVEL),
+    ("deadly", DEADLY),
+];
+
+pub struct Platform {
+    pub solid: Solid,
+    pub initial_position: Vec2,
+    pub move_sequence: Vec<PlatformMove>,
+    pub playback: Playback,
+    pub move_index: usize,
+    pub move_timer: i32,
+    /// Where the current move started, moves are relative to it.
+    pub move_start: Vec2,
+    /// Playing the move sequence backwards, see `Playback::PingPong`.
+    pub reversed: bool,
+    pub trigger: Trigger,
+    pub return_home: Option<ReturnHome>,
+    /// Triggered platforms wait for their trigger before moving.
+    pub waiting: bool,
+    /// Steps since the trigger fired.
+    pub active_timer: i32,
+    pub crumble: Option<Crumble>,
+    pub crumble_state: CrumbleState,
+    /// Steps the move sequence is ahead, applied on `reset` and to the clock of the group.
+    pub phase: i32,
+    /// Index of the sync group the platform moves with.
+    pub group: Option<usize>,
+    pub door: Option<Door>,
+}
+
+/// A platform that is only there depending on a channel.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Door {
+    pub channel: usize,
+    /// Open, so not there, while the channel is on, otherwise only while it is off.
+    pub opens: bool,
+}
+
+/// Steps a crumbling platform falls before it is gone.
+const CRUMBLE_FALL_STEPS: i32 = 120;
+
+/// A platform that gives way some time after something landed on it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Crumble {
+    /// Steps it shakes before giving way.
+    pub shake: i32,
+    /// Falls down instead of vanishing.
+    pub falls: bool,
+    /// Steps until it is back after giving way, `None` to only bring it back with `reset`.
+    pub respawn: Option<i32>,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum CrumbleState {
+    #[default]
+    Intact,
+    Shaking(i32),
+    Falling {
+        timer: i32,
+        dy: f32,
+    },
+    Gone(i32),
+}
+
+/// What starts the move sequence of a platform.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Trigger {
+    /// Moving from the start.
+    #[default]
+    Always,
+    /// Once something lands on it.
+    Stand,
+    /// Once the signal with this name fires, from a sensor or a switch.
+    Signal(String),
+}
+
+/// Takes a triggered platform back to where it waits for its trigger.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ReturnHome {
+    /// Steps after the trigger fired.
+    pub after: i32,
+    /// Steps the way back takes.
+    pub steps: i32,
+}
+
+#[derive(Clone)]
+pub enum PlatformMove {
+    ToTarget {
+        target: Vec2,
+        steps: i32,
+        easing: Easing,
+    },
+    Pause {
+        steps: i32,
+    },
+    /// Along the lines through `points` at constant speed, back to the start if `closed`.
+    Path {
+        points: Vec<Vec2>,
+        closed: bool,
+        steps: i32,
+    },
+    /// Around `center` at `radius`, turning by `speed` radians per step. Starts at the angle the
+    /// platform is at when the move begins.
+    Orbit {
+        center: Vec2,
+        radius: f32,
+        speed: f32,
+        steps: i32,
+    },
+    /// Along a curve from where the platform is through, or towards, `points`.
+    Spline {
+        kind: SplineKind,
+        points: Vec<Vec2>,
+        steps: i32,
+    },
+}
+
+/// Position and move phase of a platform, to put it back there later.
+#[derive(Copy, Clone)]
+pub struct PlatformState {
+    pub position: Vec2,
+    pub move_index: usize,
+    pub move_timer: i32,
+    pub move_start: Vec2,
+    pub reversed: bool,
+    pub waiting: bool,
+    pub active_timer: i32,
+    pub crumble_state: CrumbleState,
+}
+
+impl PlatformMove {
+    pub fn steps(&self) -> i32 {
+        match self {
+            PlatformMove::ToTarget { steps, .. }
+            | PlatformMove::Pause { steps }
+            | PlatformMove::Path { steps, .. }
+            | PlatformMove::Orbit { steps, .. }
+            | PlatformMove::Spline { steps, .. } => *steps,
+        }
+    }
+
+    /// Where a platform is `timer` steps into the move when it started at `start`.
+    pub fn position(&self, start: Vec2, timer: i32) -> Vec2 {
+        let t = timer as f32 / self.steps() as f32;
+        match self {
+            PlatformMove::ToTarget { target, easing, .. } => start.lerp(*target, easing.apply(t)),
+            PlatformMove::Pause { .. } => start,
+            PlatformMove::Path { points, closed, .. } => {
+                let mut line = vec![start];
+                line.extend_from_slice(points);
+                if *closed {
+                    line.push(start);
+                }
+                polyline_at(&line, t)
+            }
+            PlatformMove::Orbit {
+                center,
+                radius,
+                speed,
+                ..
+            } => {
+                let angle = (start.y - center.y).atan2(start.x - center.x) + speed * timer as f32;
+                *center + vec2(angle.cos(), angle.sin()) * *radius
+            }
+            PlatformMove::Spline { kind, points, .. } => {
+                let mut curve = vec![start];
+                curve.extend_from_slice(points);
+                kind.at(&curve, t)
+            }
+        }
+    }
+}
+
+impl Platform {
+    pub fn new(world: &mut World, pos: Vec2, size: Vec2, flags: u16) -> Self {
+        debug_assert_eq!(size.as_ivec2() % 16, IVec2::ZERO);
+        let solid = world.add_solid(pos, size, flags);
+        Platform {
+            solid,
+            initial_position: pos,
+            move_sequence: Vec::new(),
+            playback: Playback::Loop,
+            move_index: 0,
+            move_timer: 0,
+            move_start: pos,
+            reversed: false,
+            trigger: Trigger::Always,
+            return_home: None,
+            waiting: false,
+            active_timer: 0,
+            crumble: None,
+            crumble_state: CrumbleState::Intact,
+            phase: 0,
+            group: None,
+            door: None,
+        }
+    }
+
+    pub fn reset(&mut self, world: &mut World) {
+        self.move_index = 0;
+        self.move_timer = 0;
+        self.move_start = self.initial_position;
+        self.reversed = false;
+        self.waiting = self.trigger != Trigger::Always;
+        self.active_timer = 0;
+        self.crumble_state = CrumbleState::Intact;
+        world.set_solid_enabled(self.solid, true);
+        // Also for triggered platforms, which wait where the phase puts them
+        let position = self.seek(self.phase);
+        world.set_solid_pos(self.solid, position);
+    }
+
+    /// Puts the move sequence `time` steps in, as if played from the initial position, and
+    /// returns where the platform is then.
+    fn seek(&mut self, time: i32) -> Vec2 {
+        let steps: i32 = self.move_sequence.iter().map(PlatformMove::steps).sum();
+        self.move_index = 0;
+        self.move_timer = 0;
+        self.move_start = self.initial_position;
+        self.reversed = false;
+        if steps == 0 {
+            return self.initial_position;
+        }
+        let starts = self.move_starts();
+        let time = match self.playback {
+            Playback::Loop => time % steps,
+            Playback::PingPong => {
+                // Backwards, moves are entered at their end rather than their start
+                let time = time % (steps * 2);
+                self.reversed = time >= steps;
+                if self.reversed {
+                    steps * 2 - time
+                } else {
+                    time
+                }
+            }
+            Playback::Once if time >= steps => {
+                let last = self.move_sequence.len() - 1;
+                self.move_index = self.move_sequence.len();
+                let end = &self.move_sequence[last];
+                return end.position(starts[last], end.steps());
+            }
+            Playback::Once => time,
+        };
+        let mut move_end = 0;
+        for (index, movement) in self.move_sequence.iter().enumerate() {
+            let move_begin = move_end;
+            move_end += movement.steps();
+            let inside = if self.reversed {
+                time <= move_end
+            } else {
+                time < move_end
+            };
+            if inside {
+                self.move_index = index;
+                self.move_start = starts[index];
+                self.move_timer = if self.reversed {
+                    move_end - time
+                } else {
+                    time - move_begin
+                };
+                return movement.position(starts[index], time - move_begin);
+            }
+        }
+        self.initial_position
+    }
+
+    pub fn state(&self, world: &World) -> PlatformState {
+        PlatformState {
+            position: world.solid_pos(self.solid),
+            move_index: self.move_index,
+            move_timer: self.move_timer,
+            move_start: self.move_start,
+            reversed: self.reversed,
+            waiting: self.waiting,
+            active_timer: self.active_timer,
+            crumble_state: self.crumble_state,
+        }
+    }
+
+    pub fn restore(&mut self, world: &mut World, state: &PlatformState) {
+        self.move_index = state.move_index;
+        self.move_timer = state.move_timer;
+        self.move_start = state.move_start;
+        self.reversed = state.reversed;
+        self.waiting = state.waiting;
+        self.active_timer = state.active_timer;
+        self.crumble_state = state.crumble_state;
+        let gone = matches!(state.crumble_state, CrumbleState::Gone(_));
+        world.set_solid_enabled(self.solid, !gone);
+        world.set_solid_pos(self.solid, state.position);
+    }
+
+    fn then(self, movement: PlatformMove) -> Self {
+        let mut move_sequence = self.move_sequence;
+        move_sequence.push(movement);
+        Platform {
+            move_sequence,
+            ..self
+        }
+    }
+
+    pub fn then_moving(self, target: Vec2, steps: i32) -> Self {
+        self.then_moving_eased(target, steps, Easing::Linear)
+    }
+
+    pub fn then_moving_eased(self, target: Vec2, steps: i32, easing: Easing) -> Self {
+        self.then(PlatformMove::ToTarget {
+            target,
+            steps,
+            easing,
+        })
+    }
+
+    pub fn then_pausing(self, steps: i32) -> Self {
+        self.then(PlatformMove::Pause { steps })
+    }
+
+    pub fn then_following(self, points: Vec<Vec2>, closed: bool, steps: i32) -> Self {
+        self.then(PlatformMove::Path {
+            points,
+            closed,
+            steps,
+        })
+    }
+
+    pub fn then_orbiting(self, center: Vec2, radius: f32, speed: f32, steps: i32) -> Self {
+        self.then(PlatformMove::Orbit {
+            center,
+            radius,
+            speed,
+            steps,
+        })
+    }
+
+    pub fn then_curving(self, kind: SplineKind, points: Vec<Vec2>, steps: i32) -> Self {
+        self.then(PlatformMove::Spline {
+            kind,
+            points,
+            steps,
+        })
+    }
+
+    pub fn playing(self, playback: Playback) -> Self {
+        Platform { playback, ..self }
+    }
+
+    pub fn triggered_by(self, trigger: Trigger) -> Self {
+        Platform {
+            waiting: trigger != Trigger::Always,
+            trigger,
+            ..self
+        }
+    }
+
+    pub fn returning_home(self, after: i32, steps: i32) -> Self {
+        Platform {
+            return_home: Some(ReturnHome { after, steps }),
+            ..self
+        }
+    }
+
+    /// Puts the platform `steps` ahead in its move sequence, for platforms that share a
+    /// sequence but should not move in step.
+    pub fn with_phase(mut self, world: &mut World, steps: i32) -> Self {
+        self.phase = steps;
+        self.reset(world);
+        self
+    }
+
+    /// Launches actors landing on the platform up with `speed`, however fast they fell.
+    pub fn bouncing(self, world: &mut World, speed: f32) -> Self {
+        world.set_solid_bounce(self.solid, -speed);
+        self
+    }
+
+    pub fn with_surface(self, world: &mut World, surface: Surface) -> Self {
+        world.set_solid_surface(self.solid, surface);
+        self
+    }
+
+    pub fn door(self, door: Door) -> Self {
+        Platform {
+            door: Some(door),
+            ..self
+        }
+    }
+
+    pub fn crumbling(self, crumble: Crumble) -> Self {
+        Platform {
+            crumble: Some(crumble),
+            ..self
+        }
+    }
+
+    /// Starts a waiting platform, platforms that already move are not affected.
+    pub fn start(&mut self) {
+        if self.waiting {
+            self.waiting = false;
+            self.active_timer = 0;
+        }
+    }
+
+    /// Whether anything but a diamond stands on top of the platform.
+    fn stood_on(&self, world: &World) -> bool {
+        let rect = world.solid_collider(self.solid).as_rect();
+        world.actors().any(|(_, collider)| {
+            let actor = collider.as_rect();
+            collider.flags & BODY != 0
+                && (actor.bottom() - rect.top()).abs() < 1.0
+                && actor.right() > rect.left()
+                && actor.left() < rect.right()
+        })
+    }
+
+    /// Number of steps until the move sequence repeats, 0 for static platforms. With
+    /// `Playback::Once` the steps until the platform stops.
+    pub fn period(&self) -> i32 {
+        let steps: i32 = self.move_sequence.iter().map(PlatformMove::steps).sum();
+        match self.playback {
+            Playback::PingPong => steps * 2,
+            Playback::Loop | Playback::Once => steps,
+        }
+    }
+
+    /// Where each move of the sequence starts when played forwards from the initial position.
+    fn move_starts(&self) -> Vec<Vec2> {
+        let mut start = self.initial_position;
+        self.move_sequence
+            .iter()
+            .map(|movement| {
+                let move_start = start;
+                start = movement.position(start, movement.steps());
+                move_start
+            })
+            .collect()
+    }
+
+    /// Shakes, drops and brings back crumbling platforms. Returns whether the platform has given
+    /// way, which stops its move sequence.
+    fn step_crumble(&mut self, world: &mut World, crumble: Crumble) -> bool {
+        match self.crumble_state {
+            CrumbleState::Intact => {
+                if self.stood_on(world) {
+                    self.crumble_state = CrumbleState::Shaking(0);
+                }
+                false
+            }
+            CrumbleState::Shaking(timer) => {
+                if timer % 4 == 0 {
+                    let rect = world.solid_collider(self.solid).as_rect();
+                    world.add_particle(
+                        vec2(gen_range(rect.left(), rect.right()), rect.bottom()),
+                        vec2(gen_range(-0.5, 0.5), gen_range(0.5, 2.0)),
+                    );
+                }
+                self.crumble_state = if timer < crumble.shake {
+                    CrumbleState::Shaking(timer + 1)
+                } else if crumble.falls {
+                    CrumbleState::Falling { timer: 0, dy: 0.0 }
+                } else {
+                    world.set_solid_enabled(self.solid, false);
+                    CrumbleState::Gone(0)
+                };
+                false
+            }
+            CrumbleState::Falling { timer, dy } => {
+                let dy = dy + GRAVITY.y;
+                world.solid_move(self.solid, vec2(0.0, dy));
+                self.crumble_state = if timer < CRUMBLE_FALL_STEPS {
+                    CrumbleState::Falling {
+                        timer: timer + 1,
+                        dy,
+                    }
+                } else {
+                    world.set_solid_enabled(self.solid, false);
+                    CrumbleState::Gone(0)
+                };
+                true
+            }
+            CrumbleState::Gone(timer) => {
+                let Some(respawn) = crumble.respawn else {
+                    return true;
+                };
+                // Wait for the scavenger to get out of the way
+                let home = Rect::new(
+                    self.initial_position.x,
+                    self.initial_position.y,
+                    world.solid_collider(self.solid).dimension.x,
+                    world.solid_collider(self.solid).dimension.y,
+                );
+                if timer >= respawn && !occupied(world, home) {
+                    self.reset(world);
+                } else {
+                    self.crumble_state = CrumbleState::Gone(timer + 1);
+                }
+                true
+            }
+        }
+    }
+
+    /// Moves the platform of a sync group to where its sequence is at `clock` steps, plus its
+    /// phase. Triggered platforms of the group move on their own once started.
+    pub fn step_synced(&mut self, world: &mut World, clock: i32) {
+        if self.trigger != Trigger::Always {
+            return self.step(world);
+        }
+        if let Some(crumble) = self.crumble {
+            if self.step_crumble(world, crumble) {
+                return;
+            }
+        }
+        let position = self.seek(clock + self.phase);
+        world.solid_move(self.solid, position - world.solid_pos(self.solid));
+    }
+
+    pub fn step(&mut self, world: &mut World) {
+        if let Some(crumble) = self.crumble {
+            if self.step_crumble(world, crumble) {
+                return;
+            }
+        }
+        if self.waiting {
+            if self.trigger == Trigger::Stand && self.stood_on(world) {
+                self.start();
+            } else {
+                return;
+            }
+        }
+        if let (Trigger::Stand | Trigger::Signal(_), Some(home)) = (&self.trigger, self.return_home)
+        {
+            self.active_timer += 1;
+            if self.active_timer > home.after {
+                let timer = self.active_timer - home.after;
+                if timer == 1 {
+                    self.move_start = world.solid_pos(self.solid);
+                }
+                // Back to where the phase puts it, the sequence goes on from there next time
+                let returning_from = self.move_start;
+                let waits_at = self.seek(self.phase);
+                let position = returning_from.lerp(waits_at, timer as f32 / home.steps as f32);
+                world.solid_move(self.solid, position - world.solid_pos(self.solid));
+                if timer < home.steps {
+                    self.move_start = returning_from;
+                } else {
+                    world.set_solid_pos(self.solid, waits_at);
+                    self.waiting = true;
+                }
+                return;
+            }
+        }
+        let Some(current) = self.move_sequence.get(self.move_index) else {
+            return;
+        };
+        if self.move_timer == 0 {
+            self.move_start = if self.reversed {
+                self.move_starts()[self.move_index]
+            } else {
+                world.solid_pos(self.solid)
+            };
+        }
+        self.move_timer += 1;
+        let timer = if self.reversed {
+            current.steps() - self.move_timer
+        } else {
+            self.move_timer
+        };
+        let position = current.position(self.move_start, timer);
+        world.solid_move(self.solid, position - world.solid_pos(self.solid));
+        if self.move_timer >= current.steps() {
+            // Land exactly at the end of the move, so repeating the sequence does not drift
+            world.set_solid_pos(self.solid, position);
+            self.move_timer = 0;
+            let last = self.move_sequence.len() - 1;
+            match self.playback {
+                Playback::Loop => {
+                    self.move_index = (self.move_index + 1) % self.move_sequence.len()
+                }
+                // Runs past the end of the sequence and stops
+                Playback::Once => self.move_index += 1,
+                Playback::PingPong if self.reversed => {
+                    if self.move_index == 0 {
+                        self.reversed = false;
+                    } else {
+                        self.move_index -= 1;
+                    }
+                }
+                Playback::PingPong => {
+                    if self.move_index == last {
+                        self.reversed = true;
+                    } else {
+                        self.move_index += 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// What a signal does to a sync group.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GroupAction {
+    Pause,
+    Resume,
+    Toggle,
+    /// Resets the platforms of the group and its clock.
+    Restart,
+}
+
+/// Platforms sharing a clock, so they are paused, resumed and restarted together.
+#[derive(Clone, Debug, Default)]
+pub struct SyncGroup {
+    pub name: String,
+    /// Steps the group moved since it was last restarted.
+    pub clock: i32,
+    pub paused: bool,
+    pub starts_paused: bool,
+    /// Signals and what they do to the group.
+    pub actions: Vec<(String, GroupAction)>,
+}
+
+impl SyncGroup {
+    pub fn reset(&mut self) {
+        self.clock = 0;
+        self.paused = self.starts_paused;
+    }
+}
+
+/// On or off state switches, keys and doors are wired together with.
+#[derive(Clone, Debug, Default)]
+pub struct Channel {
+    pub name: String,
+    pub on: bool,
+}
+
+/// A key, lever or pressure plate, which one is in the flags of `actor`.
+pub struct Switch {
+    pub actor: Actor,
+    pub channel: usize,
+}
+
+/// True if the scavenger or an enemy is in `rect`, solids do not reappear on top of them.
+fn occupied(world: &World, rect: Rect) -> bool {
+    world
+        .actors()
+        .any(|(_, collider)| collider.flags & BODY != 0 && collider.as_rect().overlaps(&rect))
+}
+
+/// Opens and closes the doors as their channels say. Doors stay open while something is in
+/// the way of closing them.
+pub fn apply_channels(world: &mut World, platforms: &[Platform], channels: &[Channel]) {
+    for platform in platforms {
+        if let Some(door) = platform.door {
+            let closed = channels[door.channel].on != door.opens;
+            if closed && !world.solid_enabled(platform.solid) {
+                let rect = world.solid_collider(platform.solid).as_rect();
+                if occupied(world, rect) {
+                    continue;
+                }
+            }
+            world.set_solid_enabled(platform.solid, closed);
+        }
+    }
+}
+
+/// Moves every platform one step, those in sync groups as the clock of their group says.
+/// Platforms in paused groups stay where they are.
+pub fn step_platforms(world: &mut World, platforms: &mut [Platform], groups: &mut [SyncGroup]) {
+    for group in groups.iter_mut().filter(|group| !group.paused) {
+        group.clock += 1;
+    }
+    for platform in platforms.iter_mut() {
+        match platform.group.map(|group| &groups[group]) {
+            Some(group) if group.paused => {}
+            Some(group) => platform.step_synced(world, group.clock),
+            None => platform.step(world),
+        }
+    }
+}
+
+/// Starts the platforms waiting for `signal` and applies it to the sync groups.
+pub fn fire(world: &mut World, platforms: &mut [Platform], groups: &mut [SyncGroup], signal: &str) {
+    for platform in platforms.iter_mut() {
+        if matches!(&platform.trigger, Trigger::Signal(name) if name == signal) {
+            platform.start();
+        }
+    }
+    for (index, group) in groups.iter_mut().enumerate() {
+        for (_, action) in group.actions.iter().filter(|(name, _)| name == signal) {
+            match action {
+                GroupAction::Pause => group.paused = true,
+                GroupAction::Resume => group.paused = false,
+                GroupAction::Toggle => group.paused = !group.paused,
+                GroupAction::Restart => {
+                    group.clock = 0;
+                    for platform in platforms.iter_mut() {
+                        if platform.group == Some(index) {
+                            platform.reset(world);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Area around a soul-stone at `position` that activates it when touched.
+pub fn soul_stone_rect(position: Vec2) -> Rect {
+    Rect::new(position.x - 32.0, position.y, 64.0, 32.0)
+}
+
+/// Adds a diamond with its top left corner at `position`.
+pub fn add_coin(world: &mut World, position: Vec2) -> Actor {
+    world.add_actor(position, vec2(32.0, 32.0), COIN | NOT_TAKEN)
+}
+
+/// Places diamonds on roughly 30% of the non-deadly platforms.
+pub fn scatter_coins(world: &mut World, platforms: &[Platform]) -> Vec<Actor> {
+    let mut coins = Vec::new();
+    for platform in platforms.iter() {
+        if world.solid_has_flag(platform.solid, DEADLY) {
+            continue;
+        }
+        if gen_range(0.0, 1.0) > 0.7 {
+            let collider = world.solid_collider(platform.solid);
+            if collider.dimension.x >= 32.0 {
+                let x = gen_range(16.0, collider.dimension.x - 16.0) + collider.position.x - 16.0;
+                coins.push(add_coin(world, vec2(x, collider.position.y - 32.0)));
+            }
+        }
+    }
+    coins
+}
+
+#[derive(Clone)]
+pub struct PlatformDesc {
+    /// Line in the level file, 0 if the platform was not loaded from a file.
+    pub line: usize,
+    pub position: Vec2,
+    pub size: Vec2,
+    pub flags: u16,
+    pub moves: Vec<PlatformMove>,
+    pub playback: Playback,
+    pub trigger: Trigger,
+    pub return_home: Option<ReturnHome>,
+    pub crumble: Option<Crumble>,
+    pub phase: i32,
+    pub group: Option<usize>,
+    /// Upwards speed of actors landing on the platform.
+    pub bounce: Option<f32>,
+    pub surface: Option<Surface>,
+    pub door: Option<Door>,
+}
+
+impl PlatformDesc {
+    pub fn new(position: Vec2, size: Vec2, flags: u16) -> Self {
+        PlatformDesc {
+            line: 0,
+            position,
+            size,
+            flags,
+            moves: Vec::new(),
+            playback: Playback::Loop,
+            trigger: Trigger::Always,
+            return_home: None,
+            crumble: None,
+            phase: 0,
+            group: None,
+            bounce: None,
+            surface: None,
+            door: None,
+        }
+    }
+
+    pub fn rect(&self) -> Rect {
+        Rect::new(self.position.x, self.position.y, self.size.x, self.size.y)
+    }
+
+    pub fn build(&self, world: &mut World) -> Platform {
+        let mut platform = Platform::new(world, self.position, self.size, self.flags);
+        platform.move_sequence = self.moves.clone();
+        platform.playback = self.playback;
+        platform.return_home = self.return_home;
+        platform.crumble = self.crumble;
+        platform.group = self.group;
+        platform.door = self.door;
+        if let Some(speed) = self.bounce {
+            world.set_solid_bounce(platform.solid, -speed);
+        }
+        if let Some(surface) = self.surface {
+            world.set_solid_surface(platform.solid, surface);
+        }
+        let platform = platform.triggered_by(self.trigger.clone());
+        if self.phase > 0 {
+            platform.with_phase(world, self.phase)
+        } else {
+            platform
+        }
+    }
+}
+
+/// Ladder or rope as given in the level file.
+pub struct ClimbableDesc {
+    pub rect: Rect,
+    pub kind: ClimbKind,
+    /// Index of the platform a rope hangs from and moves with.
+    pub platform: Option<usize>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PickupKind {
+    /// Teaches the scavenger the ability.
+    Ability(Ability),
+    /// Gives back hit points in the health mode.
+    Heal(i32),
+    /// Extra life when playing with limited lives.
+    Life,
+}
+
+pub struct PickupDesc {
+    pub position: Vec2,
+    pub kind: PickupKind,
+}
+
+pub struct Pickup {
+    pub actor: Actor,
+    pub kind: PickupKind,
+}
+
+/// Key, lever or pressure plate as given in the level file, `kind` is its actor flag.
+pub struct SwitchDesc {
+    pub position: Vec2,
+    pub kind: u16,
+    pub channel: usize,
+}
+
+/// Area that fires the signal `name` when the scavenger enters it.
+pub struct Sensor {
+    pub name: String,
+    pub rect: Rect,
+}
+
+pub struct LevelText {
+    pub position: Vec2,
+    pub size: f32,
+    pub text: String,
+}
+
+/// Requirement for playing a level from the level select.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Unlock {
+    /// Reach the ground of the level with this name.
+    Finish(String),
+    /// Collect this many diamonds, counting the best finished run of each level.
+    Diamonds(u32),
+}
+
+/// A level as described by a level file, before anything is added to a `World`.
+///
+/// Level files are line based, `#` starts a comment:
+///
+/// ```text
+/// name The Chimney
+/// author Scavenger Drop
+/// par 60
+/// unlock finish The Tower
+/// keep platforms
+/// start 0 0
+/// checkpoint 600 3995
+/// platform -470 1727 96 32 no_slide
+///   move 300 1727 300 sine_in_out
+///   pause 60
+///   orbit 300 1827 100 90 240
+///   playback ping_pong
+///   trigger stand
+///   return 900 120
+/// text -80 -52 24 My trusty old soul-stone.
+/// ```
+///
+/// Level lines:
+///
+/// - `name NAME`, `author NAME` and `par SECONDS`
+/// - `unlock finish LEVEL_NAME|diamonds COUNT`, may be repeated, all of them have to be met
+/// - `controller classic|assist` picks the tuning of the controls, `classic` if not given
+/// - `start X Y` where the scavenger starts
+/// - `checkpoint X Y` a soul-stone the scavenger returns to once it was touched, placed like
+///   `start`. They are listed in the order of the descent, touching one before the active one
+///   does nothing
+/// - `keep -|platforms|diamonds`, `|` separated, what is not reset when dying after a checkpoint
+/// - `ability dash|double_jump|glide` the scavenger starts with
+/// - `health [MAX]` plays the level in the health mode with `MAX` hit points, 100 if not given,
+///   where hard landings and hazards hurt instead of killing at once. `MAX` has to be positive
+/// - `text X Y SIZE TEXT`
+///
+/// `platform X Y W H FLAGS` adds a platform, flags are `-` or a `|` separated list of
+/// `no_slide`, `ground` and `deadly`. `move`, `pause`, `path`, `orbit` and `spline` lines extend
+/// the move sequence of the platform above them, each starting where the one before ended:
+///
+/// - `move X Y STEPS [EASING]` with `linear`, `quad_in`, `quad_out`, `quad_in_out`, the same for
+///   `cubic` and `sine`, `bounce` or `elastic`
+/// - `pause STEPS`
+/// - `path open|closed STEPS X Y ...` at constant speed, `closed` returns to the start
+/// - `orbit CENTER_X CENTER_Y RADIUS DEGREES_PER_SECOND STEPS` around the center at `RADIUS`,
+///   the platform has to be that far from it when the orbit begins
+/// - `spline catmull_rom|bezier STEPS X Y ...`
+///
+/// These lines change the platform above them:
+///
+/// - `playback loop|ping_pong|once` what happens at the end of the sequence, `loop` if not given
+/// - `trigger stand|signal NAME` keeps it waiting until something lands on it or the signal fires
+/// - `return AFTER STEPS` after `trigger` takes it back to wait again `AFTER` steps after it was
+///   triggered
+/// - `crumble SHAKE_STEPS vanish|fall [RESPAWN_STEPS]` gives way after something landed on it,
+///   it comes back after `RESPAWN_STEPS` or on death
+/// - `bounce SPEED` launches the scavenger landing on it up with `SPEED` pixels per step, even
+///   from a fall that would be fatal anywhere else
+/// - `surface conveyor SPEED|ice|sticky`, conveyors carry the scavenger `SPEED` pixels per step
+///   to the right, to the left if negative, ice is slippery and sticky slows down wall slides
+/// - `phase STEPS` starts it that far into its sequence
+/// - `sync GROUP` moves it on the clock of the group
+/// - `door CHANNEL [opens|closes]` makes it a door that opens, or closes, while the channel is on
+///
+/// Signals and channels:
+///
+/// - `sensor NAME X Y W H` fires the signal when the scavenger enters the area
+/// - `on SIGNAL pause|resume|toggle|restart GROUP` what the signal does to a sync group
+/// - `paused GROUP` keeps the group still until resumed
+/// - `channel NAME on|off` how the channel starts, channels that are not listed start off
+/// - `key X Y CHANNEL` turns the channel on when taken, `lever X Y CHANNEL` switches it on and
+///   off when touched and `plate X Y CHANNEL` keeps it on while the scavenger is on it
+///
+/// Areas, their sizes have to be positive:
+///
+/// - `wind X Y W H FX FY [constant|cycle STEPS|noise STEPS]` pushes the scavenger by `FX FY`
+///   pixels per step, either always, rising and falling every `STEPS` or changing at random
+///   about every `STEPS`
+/// - `water X Y W H` and `hay X Y W H` slow down the scavenger, falling into them is safe from
+///   any height
+/// - `ladder X Y W H` can be climbed, as can `rope X Y LENGTH [attached]`, which hangs down from
+///   `X Y` and moves with the platform above it when `attached`
+///
+/// Enemies, hazards and pickups:
+///
+/// - `patrol X Y SPEED` walks along a floor, `bat X Y RANGE SPEED` swoops at the scavenger once
+///   it is closer than `RANGE` and `turret X Y DX DY INTERVAL` shoots every `INTERVAL` steps.
+///   They kill on contact, or throw the scavenger back with `knockback` at the end of the line
+/// - `rocks X Y INTERVAL [OFFSET]` drops a deadly rock every `INTERVAL` steps, the first
+///   `OFFSET` steps later, with dust trickling down shortly before
+/// - `pickup X Y ABILITY` teaches the ability when touched
+/// - `heal X Y AMOUNT` gives back hit points in the health mode, `AMOUNT` has to be positive
+/// - `life X Y` is an extra life when playing with limited lives
+#[derive(Default)]
+pub struct LevelDesc {
+    pub name: String,
+    pub author: String,
+    /// Par time in steps.
+    pub par: Option<u32>,
+    pub unlocks: Vec<Unlock>,
+    pub start: Vec2,
+    pub checkpoints: Vec<Vec2>,
+    /// Platforms keep moving instead of going back to their initial position when respawning
+    /// at a checkpoint.
+    pub keep_platforms: bool,
+    /// Diamonds taken after the last checkpoint stay taken when respawning there.
+    pub keep_diamonds: bool,
+    /// Where the diamonds are, scattered over the platforms if the level does not say.
+    pub coins: Option<Vec<Vec2>>,
+    pub controller: ControllerConfig,
+    pub platforms: Vec<PlatformDesc>,
+    pub sensors: Vec<Sensor>,
+    pub winds: Vec<Wind>,
+    pub volumes: Vec<Volume>,
+    pub climbables: Vec<ClimbableDesc>,
+    pub switches: Vec<SwitchDesc>,
+    pub enemies: Vec<EnemyDesc>,
+    pub rockfalls: Vec<RockfallDesc>,
+    /// Abilities the scavenger starts with.
+    pub abilities: Abilities,
+    pub pickups: Vec<PickupDesc>,
+    /// Hit points in the health mode, classic instant deaths if not set.
+    pub health: Option<i32>,
+    pub channels: Vec<Channel>,
+    pub groups: Vec<SyncGroup>,
+    pub texts: Vec<LevelText>,
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+struct Args<'a> {
+    line: usize,
+    words: std::str::SplitWhitespace<'a>,
+}
+
+impl<'a> Args<'a> {
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn word(&mut self, what: &str) -> Result<&'a str, ParseError> {
+        self.words
+            .next()
+            .ok_or_else(|| self.error(format!("missing {}", what)))
+    }
+
+    fn number<T: std::str::FromStr>(&mut self, what: &str) -> Result<T, ParseError> {
+        let word = self.word(what)?;
+        word.parse()
+            .map_err(|_| self.error(format!("invalid {} '{}'", what, word)))
+    }
+
+    fn vec2(&mut self, what: &str) -> Result<Vec2, ParseError> {
+        Ok(vec2(self.number(what)?, self.number(what)?))
+    }
+
+    /// Width and height of an area, both positive.
+    fn size(&mut self) -> Result<Vec2, ParseError> {
+        let size = self.vec2("size")?;
+        if size.x <= 0.0 || size.y <= 0.0 {
+            return Err(self.error("size must be positive"));
+        }
+        Ok(size)
+    }
+
+    /// The `X Y` pairs up to the end of the line, at least one.
+    fn points(&mut self) -> Result<Vec<Vec2>, ParseError> {
+        let mut points = vec![self.vec2("point")?];
+        while self.words.clone().next().is_some() {
+            points.push(self.vec2("point")?);
+        }
+        Ok(points)
+    }
+
+    fn flags(&mut self) -> Result<u16, ParseError> {
+        let word = self.word("flags")?;
+        if word == "-" {
+            return Ok(0);
+        }
+        word.split('|').try_fold(0, |flags, name| {
+            FLAG_NAMES
+                .iter()
+                .find(|(flag_name, _)| *flag_name == name)
+                .map(|(_, flag)| flags | flag)
+                .ok_or_else(|| self.error(format!("unknown flag '{}'", name)))
+        })
+    }
+
+    fn rest(self) -> String {
+        self.words.collect::<Vec<_>>().join(" ")
+    }
+
+    fn finish(mut self) -> Result<(), ParseError> {
+        match self.words.next() {
+            Some(word) => Err(self.error(format!("unexpected '{}'", word))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl LevelDesc {
+    pub fn parse(source: &str) -> Result<LevelDesc, ParseError> {
+        let mut level = LevelDesc::default();
+        for (index, line) in source.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+            let mut args = Args {
+                line: index + 1,
+                words: line.split_whitespace(),
+            };
+            let Some(keyword) = args.words.next() else {
+                continue;
+            };
+            match keyword {
+                "name" => level.name = args.rest(),
+                "author" => level.author = args.rest(),
+                "par" => {
+                    let seconds: f32 = args.number("par time")?;
+                    if seconds <= 0.0 {
+                        return Err(args.error("par time must be positive"));
+                    }
+                    level.par = Some((seconds * 60.0).round() as u32);
+                    args.finish()?;
+                }
+                "unlock" => {
+                    let unlock = match args.word("unlock rule")? {
+                        "finish" => Unlock::Finish(args.rest()),
+                        "diamonds" => {
+                            let unlock = Unlock::Diamonds(args.number("diamonds")?);
+                            args.finish()?;
+                            unlock
+                        }
+                        rule => return Err(args.error(format!("unknown unlock rule '{}'", rule))),
+                    };
+                    level.unlocks.push(unlock);
+                }
+                "start" => {
+                    level.start = args.vec2("start position")?;
+                    args.finish()?;
+                }
+                "checkpoint" => {
+                    level.checkpoints.push(args.vec2("checkpoint position")?);
+                    args.finish()?;
+                }
+                "keep" => {
+                    let word = args.word("kept state")?;
+                    for kept in word.split('|').filter(|_| word != "-") {
+                        match kept {
+                            "platforms" => level.keep_platforms = true,
+                            "diamonds" => level.keep_diamonds = true,
+                            _ => return Err(args.error(format!("cannot keep '{}'", kept))),
+                        }
+                    }
+                    args.finish()?;
+                }
+                "controller" => {
+                    let name = args.word("preset")?;
+                    level.controller = ControllerConfig::preset(name)
+                        .ok_or_else(|| args.error(format!("unknown preset '{}'", name)))?;
+                    args.finish()?;
+                }
+                "platform" => {
+                    let position = args.vec2("position")?;
+                    let size = args.vec2("size")?;
+                    if size.x <= 0.0 || size.y <= 0.0 || size.as_ivec2() % 16 != IVec2::ZERO {
+                        return Err(args.error("platform size must be a positive multiple of 16"));
+                    }
+                    level.platforms.push(PlatformDesc {
+                        line: args.line,
+                        position,
+                        size,
+                        flags: args.flags()?,
+                        moves: Vec::new(),
+                        playback: Playback::Loop,
+                        trigger: Trigger::Always,
+                        return_home: None,
+                        crumble: None,
+                        phase: 0,
+                        group: None,
+                        bounce: None,
+                        surface: None,
+                        door: None,
+                    });
+                    args.finish()?;
+                }
+                "move" | "pause" | "path" | "orbit" | "spline" => {
+                    let movement = match keyword {
+                        "move" => {
+                            let target = args.vec2("target")?;
+                            let steps = args.number("steps")?;
+                            let easing = match args.words.next() {
+                                Some(name) => Easing::from_name(name).ok_or_else(|| {
+                                    args.error(format!("unknown easing '{}'", name))
+                                })?,
+                                None => Easing::Linear,
+                            };
+                            PlatformMove::ToTarget {
+                                target,
+                                steps,
+                                easing,
+                            }
+                        }
+                        "pause" => PlatformMove::Pause {
+                            steps: args.number("steps")?,
+                        },
+                        "path" => {
+                            let closed = match args.word("path kind")? {
+                                "open" => false,
+                                "closed" => true,
+                                kind => return Err(args.error(format!("unknown path '{}'", kind))),
+                            };
+                            PlatformMove::Path {
+                                steps: args.number("steps")?,
+                                points: args.points()?,
+                                closed,
+                            }
+                        }
+                        "orbit" => {
+                            let center = args.vec2("center")?;
+                            let radius: f32 = args.number("radius")?;
+                            let degrees: f32 = args.number("angular speed")?;
+                            if radius <= 0.0 {
+                                return Err(args.error("radius must be positive"));
+                            }
+                            // The orbit begins where the platform is, which has to be on the
+                            // circle so it does not jump
+                            if let Some(platform) = level.platforms.last() {
+                                let start = platform
+                                    .moves
+                                    .iter()
+                                    .fold(platform.position, |start, movement| {
+                                        movement.position(start, movement.steps())
+                                    });
+                                let distance = start.distance(center);
+                                if (distance - radius).abs() > 0.5 {
+                                    return Err(args.error(format!(
+                                        "platform begins the orbit {} from the center, not {}",
+                                        distance, radius
+                                    )));
+                                }
+                            }
+                            PlatformMove::Orbit {
+                                center,
+                                radius,
+                                speed: degrees.to_radians() / 60.0,
+                                steps: args.number("steps")?,
+                            }
+                        }
+                        _ => {
+                            let name = args.word("spline kind")?;
+                            let kind = SplineKind::from_name(name)
+                                .ok_or_else(|| args.error(format!("unknown spline '{}'", name)))?;
+                            PlatformMove::Spline {
+                                kind,
+                                steps: args.number("steps")?,
+                                points: args.points()?,
+                            }
+                        }
+                    };
+                    if movement.steps() <= 0 {
+                        return Err(args.error("steps must be positive"));
+                    }
+                    let Some(platform) = level.platforms.last_mut() else {
+                        return Err(args.error(format!("'{}' before any platform", keyword)));
+                    };
+                    platform.moves.push(movement);
+                    args.finish()?;
+                }
+                "trigger" => {
+                    let trigger = match args.word("trigger")? {
+                        "stand" => Trigger::Stand,
+                        "signal" => Trigger::Signal(args.word("signal name")?.to_owned()),
+                        name => return Err(args.error(format!("unknown trigger '{}'", name))),
+                    };
+                    let Some(platform) = level.platforms.last_mut() else {
+                        return Err(args.error("'trigger' before any platform"));
+                    };
+                    platform.trigger = trigger;
+                    args.finish()?;
+                }
+                "return" => {
+                    let return_home = ReturnHome {
+                        after: args.number("steps before returning")?,
+                        steps: args.number("steps")?,
+                    };
+                    if return_home.after < 0 || return_home.steps <= 0 {
+                        return Err(args.error("steps must be positive"));
+                    }
+                    let Some(platform) = level.platforms.last_mut() else {
+                        return Err(args.error("'return' before any platform"));
+                    };
+                    // Untriggered platforms never wait, so there is nothing to return to
+                    if platform.trigger == Trigger::Always {
+                        return Err(args.error("'return' needs a 'trigger' before it"));
+                    }
+                    platform.return_home = Some(return_home);
+                    args.finish()?;
+                }
+                "crumble" => {
+                    let shake = args.number("shake steps")?;
+                    let falls = match args.word("crumble kind")? {
+                        "vanish" => false,
+                        "fall" => true,
+                        kind => return Err(args.error(format!("unknown crumble '{}'", kind))),
+                    };
+                    let respawn = match args.words.next() {
+                        Some(word) => Some(word.parse().map_err(|_| {
+                            args.error(format!("invalid respawn steps '{}'", word))
+                        })?),
+                        None => None,
+                    };
+                    if shake <= 0 || respawn.is_some_and(|respawn: i32| respawn <= 0) {
+                        return Err(args.error("crumble steps must be positive"));
+                    }
+                    let Some(platform) = level.platforms.last_mut() else {
+                        return Err(args.error("'crumble' before any platform"));
+                    };
+                    platform.crumble = Some(Crumble {
+                        shake,
+                        falls,
+                        respawn,
+                    });
+                    args.finish()?;
+                }
+                "sensor" => {
+                    let name = args.word("signal name")?.to_owned();
+                    let position = args.vec2("position")?;
+                    let size = args.vec2("size")?;
+                    level.sensors.push(Sensor {
+                        name,
+                        rect: Rect::new(position.x, position.y, size.x, size.y),
+                    });
+                    args.finish()?;
+                }
+                "water" | "hay" => {
+                    let medium = if keyword == "water" {
+                        Medium::Water
+                    } else {
+                        Medium::Hay
+                    };
+                    let position = args.vec2("position")?;
+                    let size = args.size()?;
+                    level.volumes.push(Volume {
+                        rect: Rect::new(position.x, position.y, size.x, size.y),
+                        medium,
+                    });
+                    args.finish()?;
+                }
+                "patrol" | "bat" | "turret" => {
+                    let position = args.vec2("position")?;
+                    let behavior = match keyword {
+                        "patrol" => Behavior::Patrol {
+                            speed: args.number("speed")?,
+                        },
+                        "bat" => Behavior::Bat {
+                            range: args.number("range")?,
+                            speed: args.number("speed")?,
+                        },
+                        _ => Behavior::Turret {
+                            velocity: args.vec2("shot velocity")?,
+                            interval: args.number("interval")?,
+                        },
+                    };
+                    if let Behavior::Turret { interval, .. } = behavior {
+                        if interval <= 0 {
+                            return Err(args.error("interval must be positive"));
+                        }
+                    }
+                    let contact = match args.words.next() {
+                        None | Some("kill") => Contact::Kill,
+                        Some("knockback") => Contact::KnockBack,
+                        Some(word) => return Err(args.error(format!("unknown contact '{}'", word))),
+                    };
+                    level.enemies.push(EnemyDesc {
+                        position,
+                        behavior,
+                        contact,
+                    });
+                    args.finish()?;
+                }
+                "ability" | "pickup" => {
+                    let position = if keyword == "pickup" {
+                        Some(args.vec2("position")?)
+                    } else {
+                        None
+                    };
+                    let name = args.word("ability")?;
+                    let ability = Ability::from_name(name)
+                        .ok_or_else(|| args.error(format!("unknown ability '{}'", name)))?;
+                    match position {
+                        Some(position) => level.pickups.push(PickupDesc {
+                            position,
+                            kind: PickupKind::Ability(ability),
+                        }),
+                        None => level.abilities.grant(ability),
+                    }
+                    args.finish()?;
+                }
+                "health" => {
+                    let health = match args.words.next() {
+                        Some(word) => word
+                            .parse()
+                            .map_err(|_| args.error(format!("invalid hit points '{}'", word)))?,
+                        None => DEFAULT_HEALTH,
+                    };
+                    if health <= 0 {
+                        return Err(args.error("hit points must be positive"));
+                    }
+                    level.health = Some(health);
+                    args.finish()?;
+                }
+                "life" => {
+                    let position = args.vec2("position")?;
+                    level.pickups.push(PickupDesc {
+                        position,
+                        kind: PickupKind::Life,
+                    });
+                    args.finish()?;
+                }
+                "heal" => {
+                    let position = args.vec2("position")?;
+                    let amount = args.number("amount")?;
+                    if amount <= 0 {
+                        return Err(args.error("amount must be positive"));
+                    }
+                    level.pickups.push(PickupDesc {
+                        position,
+                        kind: PickupKind::Heal(amount),
+                    });
+                    args.finish()?;
+                }
+                "rocks" => {
+                    let position = args.vec2("position")?;
+                    let interval = args.number("interval")?;
+                    if interval <= 0 {
+                        return Err(args.error("interval must be positive"));
+                    }
+                    let offset = match args.words.next() {
+                        Some(word) => word
+                            .parse()
+                            .map_err(|_| args.error(format!("invalid offset '{}'", word)))?,
+                        None => 0,
+                    };
+                    level.rockfalls.push(RockfallDesc {
+                        position,
+                        interval,
+                        offset,
+                    });
+                    args.finish()?;
+                }
+                "key" | "lever" | "plate" => {
+                    let kind = match keyword {
+                        "key" => KEY,
+                        "lever" => LEVER,
+                        _ => PLATE,
+                    };
+                    let position = args.vec2("position")?;
+                    let channel = level.channel(args.word("channel name")?);
+                    level.switches.push(SwitchDesc {
+                        position,
+                        kind,
+                        channel,
+                    });
+                    args.finish()?;
+                }
+                "channel" => {
+                    let channel = level.channel(args.word("channel name")?);
+                    let on = match args.word("channel state")? {
+                        "on" => true,
+                        "off" => false,
+                        state => return Err(args.error(format!("unknown state '{}'", state))),
+                    };
+                    level.channels[channel].on = on;
+                    args.finish()?;
+                }
+                "door" => {
+                    let channel = level.channel(args.word("channel name")?);
+                    let opens = match args.words.next() {
+                        None | Some("opens") => true,
+                        Some("closes") => false,
+                        Some(word) => return Err(args.error(format!("unknown door '{}'", word))),
+                    };
+                    let Some(platform) = level.platforms.last_mut() else {
+                        return Err(args.error("'door' before any platform"));
+                    };
+                    platform.door = Some(Door { channel, opens });
+                    args.finish()?;
+                }
+                "ladder" => {
+                    let position = args.vec2("position")?;
+                    let size = args.size()?;
+                    level.climbables.push(ClimbableDesc {
+                        rect: Rect::new(position.x, position.y, size.x, size.y),
+                        kind: ClimbKind::Ladder,
+                        platform: None,
+                    });
+                    args.finish()?;
+                }
+                "rope" => {
+                    let top = args.vec2("position")?;
+                    let length: f32 = args.number("rope length")?;
+                    if length <= 0.0 {
+                        return Err(args.error("rope length must be positive"));
+                    }
+                    let platform = match args.words.next() {
+                        None => None,
+                        Some("attached") if !level.platforms.is_empty() => {
+                            Some(level.platforms.len() - 1)
+                        }
+                        Some("attached") => {
+                            return Err(args.error("'rope attached' before any platform"))
+                        }
+                        Some(word) => return Err(args.error(format!("unexpected '{}'", word))),
+                    };
+                    level.climbables.push(ClimbableDesc {
+                        rect: Rect::new(top.x - 8.0, top.y, 16.0, length),
+                        kind: ClimbKind::Rope,
+                        platform,
+                    });
+                    args.finish()?;
+                }
+                "wind" => {
+                    let position = args.vec2("position")?;
+                    let size = args.size()?;
+                    let force = args.vec2("force")?;
+                    let gust = match args.words.next() {
+                        None | Some("constant") => Gust::Constant,
+                        Some(kind @ ("cycle" | "noise")) => {
+                            let period = args.number("gust steps")?;
+                            if period == 0 {
+                                return Err(args.error("gust steps must be positive"));
+                            }
+                            if kind == "cycle" {
+                                Gust::Cycle(period)
+                            } else {
+                                Gust::Noise(period)
+                            }
+                        }
+                        Some(kind) => return Err(args.error(format!("unknown gust '{}'", kind))),
+                    };
+                    level.winds.push(Wind {
+                        rect: Rect::new(position.x, position.y, size.x, size.y),
+                        force,
+                        gust,
+                    });
+                    args.finish()?;
+                }
+                "bounce" => {
+                    let speed: f32 = args.number("launch speed")?;
+                    if speed <= 0.0 {
+                        return Err(args.error("launch speed must be positive"));
+                    }
+                    let Some(platform) = level.platforms.last_mut() else {
+                        return Err(args.error("'bounce' before any platform"));
+                    };
+                    platform.bounce = Some(speed);
+                    args.finish()?;
+                }
+                "surface" => {
+                    let surface = match args.word("surface kind")? {
+                        "conveyor" => Surface::Conveyor(args.number("conveyor speed")?),
+                        "ice" => Surface::Ice,
+                        "sticky" => Surface::Sticky,
+                        name => return Err(args.error(format!("unknown surface '{}'", name))),
+                    };
+                    let Some(platform) = level.platforms.last_mut() else {
+                        return Err(args.error("'surface' before any platform"));
+                    };
+                    platform.surface = Some(surface);
+                    args.finish()?;
+                }
+                "phase" => {
+                    let phase = args.number("phase steps")?;
+                    if phase < 0 {
+                        return Err(args.error("phase must not be negative"));
+                    }
+                    let Some(platform) = level.platforms.last_mut() else {
+                        return Err(args.error("'phase' before any platform"));
+                    };
+                    platform.phase = phase;
+                    args.finish()?;
+                }
+                "sync" => {
+                    let group = level.group(args.word("group name")?);
+                    let Some(platform) = level.platforms.last_mut() else {
+                        return Err(args.error("'sync' before any platform"));
+                    };
+                    platform.group = Some(group);
+                    args.finish()?;
+                }
+                "on" => {
+                    let signal = args.word("signal name")?.to_owned();
+                    let action = match args.word("group action")? {
+                        "pause" => GroupAction::Pause,
+                        "resume" => GroupAction::Resume,
+                        "toggle" => GroupAction::Toggle,
+                        "restart" => GroupAction::Restart,
+                        action => return Err(args.error(format!("unknown action '{}'", action))),
+                    };
+                    let group = level.group(args.word("group name")?);
+                    level.groups[group].actions.push((signal, action));
+                    args.finish()?;
+                }
+                "paused" => {
+                    let group = level.group(args.word("group name")?);
+                    level.groups[group].starts_paused = true;
+                    level.groups[group].paused = true;
+                    args.finish()?;
+                }
+                "playback" => {
+                    let name = args.word("playback")?;
+                    let playback = Playback::from_name(name)
+                        .ok_or_else(|| args.error(format!("unknown playback '{}'", name)))?;
+                    let Some(platform) = level.platforms.last_mut() else {
+                        return Err(args.error("'playback' before any platform"));
+                    };
+                    platform.playback = playback;
+                    args.finish()?;
+                }
+                "text" => {
+                    let position = args.vec2("position")?;
+                    let size = args.number("font size")?;
+                    level.texts.push(LevelText {
+                        position,
+                        size,
+                        text: args.rest(),
+                    });
+                }
+                _ => return Err(args.error(format!("unknown keyword '{}'", keyword))),
+            }
+        }
+        Ok(level)
+    }
+
+    /// Index of the sync group `name`, added if there is none yet.
+    fn group(&mut self, name: &str) -> usize {
+        match self.groups.iter().position(|group| group.name == name) {
+            Some(index) => index,
+            None => {
+                self.groups.push(SyncGroup {
+                    name: name.to_owned(),
+                    ..Default::default()
+                });
+                self.groups.len() - 1
+            }
+        }
+    }
+
+    /// Index of the channel `name`, added if there is none yet.
+    fn channel(&mut self, name: &str) -> usize {
+        match self
+            .channels
+            .iter()
+            .position(|channel| channel.name == name)
+        {
+            Some(index) => index,
+            None => {
+                self.channels.push(Channel {
+                    name: name.to_owned(),
+                    ..Default::default()
+                });
+                self.channels.len() - 1
+            }
+        }
+    }
+
+    /// Adds the keys, levers and pressure plates, all 32 by 32 like the scavenger.
+    pub fn build_switches(&self, world: &mut World) -> Vec<Switch> {
+        self.switches
+            .iter()
+            .map(|switch| Switch {
+                actor: world.add_actor(switch.position, vec2(32.0, 32.0), switch.kind | NOT_TAKEN),
+                channel: switch.channel,
+            })
+            .collect()
+    }
+
+    pub fn build_enemies(&self, world: &mut World) -> Vec<Enemy> {
+        self.enemies
+            .iter()
+            .map(|enemy| enemy.build(world))
+            .collect()
+    }
+
+    /// Adds the pickups, taken ones lose `NOT_TAKEN` like diamonds.
+    pub fn build_pickups(&self, world: &mut World) -> Vec<Pickup> {
+        self.pickups
+            .iter()
+            .map(|pickup| Pickup {
+                actor: world.add_actor(pickup.position, vec2(32.0, 32.0), NOT_TAKEN),
+                kind: pickup.kind,
+            })
+            .collect()
+    }
+
+    pub fn build_rockfalls(&self) -> Vec<Rockfall> {
+        self.rockfalls.iter().map(RockfallDesc::build).collect()
+    }
+
+    pub fn build(&self, world: &mut World) -> Vec<Platform> {
+        for wind in &self.winds {
+            world.add_wind(*wind);
+        }
+        for volume in &self.volumes {
+            world.add_volume(*volume);
+        }
+        let platforms: Vec<Platform> = self
+            .platforms
+            .iter()
+            .map(|desc| desc.build(world))
+            .collect();
+        for climbable in &self.climbables {
+            let (rect, anchor) = match climbable.platform {
+                Some(index) => (
+                    climbable.rect.offset(-self.platforms[index].position),
+                    Some(platforms[index].solid),
+                ),
+                None => (climbable.rect, None),
+            };
+            world.add_climbable(Climbable {
+                rect,
+                kind: climbable.kind,
+                anchor,
+            });
+        }
+        apply_channels(world, &platforms, &self.channels);
+        platforms
+    }
+
+    /// Adds the diamonds, scattered with `seed` unless the level places them itself.
+    pub fn build_coins(&self, world: &mut World, platforms: &[Platform], seed: u64) -> Vec<Actor> {
+        match &self.coins {
+            Some(coins) => coins
+                .iter()
+                .map(|position| add_coin(world, *position))
+                .collect(),
+            None => {
+                srand(seed);
+                scatter_coins(world, platforms)
+            }
+        }
+    }
+
+    /// Diamonds in the level when they are scattered with `seed`, like the game does.
+    pub fn diamonds(&self, seed: u64) -> usize {
+        let mut world = World::new();
+        let platforms = self.build(&mut world);
+        self.build_coins(&mut world, &platforms, seed).len()
+    }
+
+    /// Top of the highest ground level platform, used as zero for the height display.
+    pub fn ground(&self) -> f32 {
+        self.platforms
+            .iter()
+            .filter(|platform| platform.flags & GROUND_LEVEL != 0)
+            .map(|platform| platform.position.y)
+            .reduce(f32::min)
+            .unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(source: &str) -> ParseError {
+        LevelDesc::parse(source)
+            .err()
+            .expect("level should not parse")
+    }
+
+    #[test]
+    fn parse_reads_platforms_and_moves() {
+        let level = LevelDesc::parse(
+            "name Test Tower # comment\nstart 0 -16\nplatform -48 32 96 32 ground|no_slide\n\
+             \x20 move 48 32 60\n  pause 30\n",
+        )
+        .unwrap();
+        assert_eq!(level.name, "Test Tower");
+        assert_eq!(level.start, vec2(0.0, -16.0));
+        let [platform] = &level.platforms[..] else {
+            panic!("expected one platform");
+        };
+        assert_eq!(platform.flags, GROUND_LEVEL | NO_SLIDE);
+        assert_eq!(platform.moves.len(), 2);
+        assert_eq!(level.ground(), 32.0);
+    }
+
+    #[test]
+    fn parse_reports_the_line() {
+        let error = error("name Test\n\nplatform 0 0 96 32 -\nfly 1 2\n");
+        assert_eq!(error.line, 4);
+        assert_eq!(error.message, "unknown keyword 'fly'");
+    }
+
+    #[test]
+    fn parse_rejects_bad_values() {
+        for source in [
+            "platform 0 0 96 32 sticky",
+            "platform 0 0 96",
+            "move 0 100 60",
+            "platform 0 0 96 32 -\n  move 0 100 0",
+        ] {
+            let error = error(source);
+            assert_eq!(error.line, source.lines().count(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn built_in_levels_parse() {
+        for source in LEVELS {
+            let level = LevelDesc::parse(source).unwrap();
+            assert!(!level.name.is_empty());
+            assert!(level.diamonds(0) > 0, "{}", level.name);
+        }
+    }
+
+    #[test]
+    fn orbits_start_where_the_platform_is() {
+        let orbit = PlatformMove::Orbit {
+            center: vec2(0.0, 100.0),
+            radius: 100.0,
+            speed: 0.1,
+            steps: 60,
+        };
+        let start = vec2(0.0, 0.0);
+        assert!(orbit.position(start, 0).distance(start) < 0.001);
+        assert!((orbit.position(start, 30).distance(vec2(0.0, 100.0)) - 100.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn orbit_radius_must_match_the_start() {
+        let source = "platform 0 0 96 32 -\n  move 0 50 60\n  orbit 0 150 100 90 240\n";
+        LevelDesc::parse(source).unwrap();
+        let off = error("platform 0 0 96 32 -\n  orbit 0 100 50 90 240");
+        assert_eq!(off.line, 2);
+        let empty = error("platform 0 0 96 32 -\n  orbit 0 0 0 90 240");
+        assert_eq!(empty.message, "radius must be positive");
+    }
+
+    #[test]
+    fn return_needs_a_trigger() {
+        let source = "platform 0 0 96 32 -\n  move 0 100 60\n  trigger stand\n  return 900 120\n";
+        let level = LevelDesc::parse(source).unwrap();
+        assert_eq!(level.platforms[0].trigger, Trigger::Stand);
+        assert_eq!(
+            level.platforms[0].return_home,
+            Some(ReturnHome {
+                after: 900,
+                steps: 120
+            })
+        );
+        let error = error("platform 0 0 96 32 -\n  move 0 100 60\n  return 900 120");
+        assert_eq!(error.line, 3);
+    }
+
+    #[test]
+    fn health_and_heal_must_be_positive() {
+        let level = LevelDesc::parse("health\nheal 0 0 25").unwrap();
+        assert_eq!(level.health, Some(DEFAULT_HEALTH));
+        assert_eq!(level.pickups[0].kind, PickupKind::Heal(25));
+        for source in ["health 0", "health -5", "heal 0 0 0", "heal 0 0 -10"] {
+            assert_eq!(error(source).line, 1, "{}", source);
+        }
+    }
+
+    #[test]
+    fn sync_groups_move_by_their_clock() {
+        let level = LevelDesc::parse(
+            "platform 0 0 96 32 -\n  move 0 60 60\n  playback ping_pong\n  sync lifts\n\
+             platform 200 0 96 32 -\n  move 0 60 60\n  playback ping_pong\n  phase 90\n  sync lifts\n\
+             platform 400 0 96 32 -\n  move 0 60 60\n  trigger signal go\n  phase 30\n\
+             on stop pause lifts\non again restart lifts\n",
+        )
+        .unwrap();
+        let mut world = World::new();
+        let mut platforms = level.build(&mut world);
+        let mut groups = level.groups.clone();
+        let y = |world: &World, platforms: &[Platform], index: usize| {
+            world.solid_pos(platforms[index].solid).y
+        };
+        assert_eq!(y(&world, &platforms, 1), 30.0);
+        assert_eq!(y(&world, &platforms, 2), 30.0);
+        for _ in 0..20 {
+            step_platforms(&mut world, &mut platforms, &mut groups);
+        }
+        assert_eq!(groups[0].clock, 20);
+        assert_eq!(y(&world, &platforms, 0), 20.0);
+        assert_eq!(y(&world, &platforms, 1), 10.0);
+        assert_eq!(y(&world, &platforms, 2), 30.0);
+        fire(&mut world, &mut platforms, &mut groups, "stop");
+        step_platforms(&mut world, &mut platforms, &mut groups);
+        assert_eq!(groups[0].clock, 20);
+        assert_eq!(y(&world, &platforms, 0), 20.0);
+        fire(&mut world, &mut platforms, &mut groups, "again");
+        assert_eq!(groups[0].clock, 0);
+        assert_eq!(y(&world, &platforms, 0), 0.0);
+        assert_eq!(y(&world, &platforms, 1), 30.0);
+        fire(&mut world, &mut platforms, &mut groups, "go");
+        step_platforms(&mut world, &mut platforms, &mut groups);
+        assert_eq!(y(&world, &platforms, 2), 31.0);
+    }
+
+    #[test]
+    fn crumble_steps_must_be_positive() {
+        let level = LevelDesc::parse("platform 0 0 96 32 -\n  crumble 30 fall 300\n").unwrap();
+        assert_eq!(
+            level.platforms[0].crumble,
+            Some(Crumble {
+                shake: 30,
+                falls: true,
+                respawn: Some(300)
+            })
+        );
+        for source in [
+            "platform 0 0 96 32 -\n  crumble 0 vanish",
+            "platform 0 0 96 32 -\n  crumble -30 fall",
+            "platform 0 0 96 32 -\n  crumble 30 vanish 0",
+        ] {
+            assert_eq!(error(source).line, 2, "{}", source);
+        }
+    }
+
+    #[test]
+    fn areas_must_have_a_size() {
+        for source in [
+            "wind 0 0 0 100 1 0",
+            "name Test\nwind 0 0 100 -16 1 0",
+            "water 0 0 -64 64",
+            "hay 0 0 64 0",
+            "ladder 0 0 32 0",
+        ] {
+            let error = error(source);
+            assert_eq!(error.line, source.lines().count(), "{}", source);
+            assert_eq!(error.message, "size must be positive");
+        }
+    }
+}
@@ -0,0 +1,11 @@
+pub mod controller;
+pub mod daily;
+pub mod enemy;
+pub mod generate;
+pub mod level;
+pub mod motion;
+pub mod physics;
+pub mod player;
+pub mod progress;
+pub mod storage;
+pub mod validate;
@@ -1,22 +1,26 @@
-mod physics;
-
 use gamepads::{Button, Gamepads};
 use macroquad::audio::*;
+use macroquad::miniquad::window::{clipboard_get, clipboard_set};
 use macroquad::prelude::*;
 use macroquad::rand::*;
-use physics::*;
+use sj6::controller::*;
+use sj6::daily::{self, CodeError, DailyResult, History};
+use sj6::enemy::*;
+use sj6::generate::*;
+use sj6::level::*;
+use sj6::physics::*;
+use sj6::player::*;
+use sj6::progress::{Progress, RunMode};
 use std::ops::RangeInclusive;
 
-const NO_SLIDE: u8 = 1;
-const GROUND_LEVEL: u8 = 2;
-const DEADLY: u8 = 4;
-const COIN: u8 = 8;
-const NOT_TAKEN: u8 = 16;
-
 enum ScavengerAnim {
     Idle,
     Run,
     Fall,
+    Climb,
+    Jump,
+    Glide,
+    Dash,
 }
 
 impl ScavengerAnim {
@@ -25,61 +29,279 @@ impl ScavengerAnim {
             Self::Idle => 1..=7,
             Self::Run => 8..=15,
             Self::Fall => 20..=23,
+            Self::Climb => 30..=35,
+            Self::Jump => 24..=27,
+            Self::Glide => 40..=43,
+            Self::Dash => 48..=50,
+        }
+    }
+}
+
+/// The soul-stone the scavenger returns to, with what to restore when it does.
+struct Checkpoint {
+    /// Index into `LevelDesc::checkpoints`.
+    index: usize,
+    position: Vec2,
+    coins: u32,
+    /// Diamonds already taken when the stone was touched.
+    taken: Vec<Actor>,
+    keys: Vec<Actor>,
+    channels: Vec<Channel>,
+}
+
+/// A moment of a practice run to go back to.
+struct SaveState {
+    position: Vec2,
+    dx: f32,
+    dy: f32,
+    climbing: bool,
+    abilities: Abilities,
+    charges: Charges,
+    grace: Grace,
+    health: Option<Health>,
+    lives: u32,
+    timer: u32,
+    coins: u32,
+    taken: Vec<Actor>,
+    platforms: Vec<PlatformState>,
+    groups: Vec<SyncGroup>,
+    keys: Vec<Actor>,
+    channels: Vec<Channel>,
+}
+
+/// Diamonds, or keys with `KEY`, the scavenger has picked up.
+fn taken(world: &World, kind: u16) -> Vec<Actor> {
+    world
+        .actors()
+        .filter(|(_, collider)| collider.flags & (kind | NOT_TAKEN) == kind)
+        .map(|(actor, _)| actor)
+        .collect()
+}
+
+/// Hit points to start with, in the health mode if the level asks for it or the player picked it.
+fn start_health(level: &LevelDesc, picked: bool) -> Option<Health> {
+    level
+        .health
+        .or(picked.then_some(DEFAULT_HEALTH))
+        .map(Health::new)
+}
+
+/// The tuning of `level` with the tuning file on top, a broken file is reported in `message`.
+fn controller_config(
+    level: &LevelDesc,
+    file: &TuningFile,
+    message: &mut String,
+) -> ControllerConfig {
+    file.config(level.controller).unwrap_or_else(|err| {
+        *message = format!("{}: {}", TUNING_FILE, err);
+        level.controller
+    })
+}
+
+/// Rows of the assist menu.
+fn assist_lines(assists: &Assists) -> [String; 5] {
+    let on_off = |on: bool| if on { "on" } else { "off" };
+    [
+        format!("Game speed: {:.0}%", assists.speed * 100.0),
+        format!("Safe drop line: {}", on_off(assists.safe_drop_line)),
+        format!(
+            "Deadly platforms are safe: {}",
+            on_off(assists.deadly_immunity)
+        ),
+        format!("Survive any fall: {}", on_off(assists.fall_immunity)),
+        format!(
+            "Slide down every wall: {}",
+            on_off(assists.slide_everywhere)
+        ),
+    ]
+}
+
+/// Puts back the heal pickups and those of abilities the scavenger does not know yet, and
+/// extra lives with `lives`.
+fn reset_pickups(world: &mut World, pickups: &[Pickup], abilities: Abilities, lives: bool) {
+    for pickup in pickups {
+        match pickup.kind {
+            PickupKind::Ability(ability) if abilities.has(ability) => {
+                world.actor_unset_flag(pickup.actor, NOT_TAKEN);
+            }
+            PickupKind::Life if !lives => {}
+            _ => world.actor_set_flag(pickup.actor, NOT_TAKEN),
+        }
+    }
+}
+
+/// How far the scavenger at `pos` got, down from the start in endless towers.
+fn height_text(tower: &Tower, level: &LevelDesc, pos: Vec2) -> String {
+    if matches!(tower, Tower::Endless { .. }) {
+        format!("Depth: {:.1}m", (pos.y - level.start.y) / 16.0)
+    } else {
+        format!("Height: {:.1}m", (level.ground() - pos.y - 32.0) / 16.0)
+    }
+}
+
+/// Puts back every diamond, or key with `KEY`, except the `taken` ones.
+fn reset_taken(world: &mut World, kind: u16, taken: &[Actor]) {
+    let actors: Vec<_> = world
+        .actors()
+        .filter(|(_, collider)| collider.flags & kind != 0)
+        .map(|(actor, _)| actor)
+        .collect();
+    for actor in actors {
+        if taken.contains(&actor) {
+            world.actor_unset_flag(actor, NOT_TAKEN);
+        } else {
+            world.actor_set_flag(actor, NOT_TAKEN);
         }
     }
 }
 
-struct Platform {
-    solid: Solid,
-    initial_position: Vec2,
-    move_sequence: Vec<PlatformMove>,
-    move_index: usize,
-    move_timer: i32,
+/// Steps a bounce pad stays pressed in after launching the scavenger.
+const SQUASH_STEPS: u32 = 12;
+/// Steps of falling the drop line looks ahead at most.
+const DROP_LINE_STEPS: u32 = 600;
+/// Lives at the start of a run with limited lives.
+const START_LIVES: u32 = 3;
+
+/// How far below the scavenger the endless tower is generated.
+const ENDLESS_LOOKAHEAD: f32 = 6000.0;
+/// Closer than this the endless tower is generated right away, even if the game stalls.
+const ENDLESS_NEAR: f32 = 1000.0;
+/// Search states spent on generating the endless tower per step otherwise, about 3ms.
+const ENDLESS_STATES_PER_STEP: usize = 200;
+/// Search states spent per frame on building a generated tower, the game waits for it.
+const BUILD_STATES_PER_FRAME: usize = 1000;
+
+enum Tower {
+    /// Index into `LEVELS`.
+    Level(usize),
+    Random(u64),
+    /// Only the first life of the day is `scored`, everything else is practice.
+    Daily {
+        day: u64,
+        scored: bool,
+    },
+    Endless {
+        seed: u64,
+        generator: Box<TowerGenerator>,
+    },
 }
 
-enum PlatformMove {
-    ToTarget { target: Vec2, steps: i32 },
-    Pause { steps: i32 },
+/// What a tower is built for.
+enum Build {
+    Play(Tower),
+    /// Counting the diamonds of the day of a result.
+    Check(DailyResult),
 }
 
-impl Platform {
-    fn new(world: &mut World, pos: Vec2, size: Vec2, flags: u8) -> Self {
-        debug_assert_eq!(size.as_ivec2() % 16, IVec2::ZERO);
-        let solid = world.add_solid(pos, size, flags);
-        Platform {
-            solid,
-            initial_position: pos,
-            move_sequence: Vec::new(),
-            move_index: 0,
-            move_timer: 0,
+impl Tower {
+    fn endless(seed: u64) -> Self {
+        Tower::Endless {
+            seed,
+            generator: Box::new(TowerGenerator::new(seed)),
         }
     }
 
-    fn reset(&mut self, world: &mut World) {
-        self.move_index = 0;
-        self.move_timer = 0;
-        world.set_solid_pos(self.solid, self.initial_position);
+    /// Picks the tower from `--seed N` or `--endless [N]`, the first level otherwise.
+    fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let seed = |index: usize| args.get(index).and_then(|seed| seed.parse().ok());
+        match args.first().map(String::as_str) {
+            Some("--seed") => seed(1).map_or(Tower::Level(0), Tower::Random),
+            Some("--endless") => Tower::endless(seed(1).unwrap_or_else(random_seed)),
+            _ => Tower::Level(0),
+        }
     }
 
-    fn then_moving(self, target: Vec2, steps: i32) -> Self {
-        let mut move_sequence = self.move_sequence;
-        move_sequence.push(PlatformMove::ToTarget { target, steps });
-        Platform {
-            move_sequence,
-            ..self
+    /// Builds the tower over several frames if it is generated up front, checking it takes
+    /// too long to do at once.
+    fn builder(&self) -> Option<TowerBuilder> {
+        match self {
+            Tower::Random(seed) => Some(TowerBuilder::new(
+                format!("Random Tower {}", seed),
+                *seed,
+                RANDOM_SECTIONS,
+            )),
+            Tower::Daily { day, .. } => Some(daily::builder(*day)),
+            _ => None,
         }
     }
 
-    fn then_pausing(self, steps: i32) -> Self {
-        let mut move_sequence = self.move_sequence;
-        move_sequence.push(PlatformMove::Pause { steps });
-        Platform {
-            move_sequence,
-            ..self
+    /// Starts the tower from scratch, with the level its builder made if it has one.
+    fn load(&mut self, built: Option<LevelDesc>) -> (LevelDesc, World, Vec<Platform>) {
+        let level = match (self, built) {
+            (_, Some(level)) => level,
+            (Tower::Level(index), None) => LevelDesc::parse(LEVELS[*index]).unwrap(),
+            (Tower::Random(seed), None) => generate(*seed, RANDOM_SECTIONS),
+            (Tower::Daily { day, .. }, None) => daily::builder(*day).build(),
+            (Tower::Endless { seed, generator }, None) => {
+                **generator = TowerGenerator::new(*seed);
+                let (start, platforms) = generator.start();
+                LevelDesc {
+                    name: format!("Endless Tower {}", seed),
+                    start,
+                    platforms,
+                    coins: Some(Vec::new()),
+                    ..Default::default()
+                }
+            }
+        };
+        let mut world = World::new();
+        let platforms = level.build(&mut world);
+        // Same diamonds as validate-level checks
+        level.build_coins(&mut world, &platforms, 0);
+        (level, world, platforms)
+    }
+
+    /// Generates more of an endless tower a little every step, checking a section takes
+    /// too long to do at once.
+    fn extend(&mut self, world: &mut World, platforms: &mut Vec<Platform>, y: f32) {
+        let Tower::Endless { generator, .. } = self else {
+            return;
+        };
+        while generator.depth() < y + ENDLESS_LOOKAHEAD {
+            let near = generator.depth() < y + ENDLESS_NEAR;
+            let budget = if near {
+                usize::MAX
+            } else {
+                ENDLESS_STATES_PER_STEP
+            };
+            match generator.work(budget) {
+                Some(section) => {
+                    for desc in section.platforms {
+                        platforms.push(desc.build(world));
+                    }
+                    for position in section.coins {
+                        add_coin(world, position);
+                    }
+                }
+                None if near => {}
+                None => break,
+            }
         }
     }
 }
 
+/// What is missing to play a locked level.
+fn requirement(unlock: &Unlock, progress: &Progress) -> String {
+    match unlock {
+        Unlock::Finish(name) => format!("finish {}", name),
+        Unlock::Diamonds(count) => format!("{} diamonds ({} so far)", count, progress.diamonds()),
+    }
+}
+
+/// Message for checking the result `code`.
+fn result_message(code: &str, result: Result<DailyResult, CodeError>) -> String {
+    match result {
+        // Codes are not signed, they can only be checked for making sense
+        Ok(result) => format!("Plausible, not proven: {}", result),
+        Err(err) => format!("Result '{}' does not add up: {}", code.trim(), err),
+    }
+}
+
+fn random_seed() -> u64 {
+    macroquad::miniquad::date::now() as u64
+}
+
 fn sfx(sound: &Sound) {
     play_sound(
         sound,
@@ -90,9 +312,37 @@ fn sfx(sound: &Sound) {
     );
 }
 
-fn down(value: &mut f32, amount: f32) -> f32 {
-    *value += amount;
-    *value
+/// Streaks drifting through a wind zone with its current force, fading when it calms down.
+fn draw_wind_streaks(wind: &Wind, index: usize, t: u32) {
+    let strength = wind.strength(t);
+    if strength < 0.05 || wind.force == Vec2::ZERO {
+        return;
+    }
+    let direction = wind.force.normalize();
+    let speed = wind.force.length() * 40.0;
+    let count = (wind.rect.w * wind.rect.h / 4096.0).ceil() as usize;
+    for streak in 0..count {
+        // Scattered by a hash so they need no state
+        let seed = (index * 7919 + streak * 104729) as u32;
+        let offset = vec2(
+            (seed.wrapping_mul(2654435761) % 1000) as f32 / 1000.0 * wind.rect.w,
+            (seed.wrapping_mul(2246822519) % 1000) as f32 / 1000.0 * wind.rect.h,
+        );
+        let travelled = direction * t as f32 * speed * (0.5 + (streak % 3) as f32 * 0.25);
+        let position = vec2(
+            (offset.x + travelled.x).rem_euclid(wind.rect.w),
+            (offset.y + travelled.y).rem_euclid(wind.rect.h),
+        ) + wind.rect.point();
+        let tail = position - direction * 24.0 * strength;
+        draw_line(
+            tail.x,
+            tail.y,
+            position.x,
+            position.y,
+            1.0,
+            Color::new(1.0, 1.0, 1.0, 0.5 * strength),
+        );
+    }
 }
 
 fn window_conf() -> Conf {
@@ -133,9 +383,9 @@ async fn main() {
     let snd_wise_crack = load_sound_from_bytes(include_bytes!("../assets/theobstacleistheway.ogg"))
         .await
         .unwrap();
-    //let snd_jump = load_sound_from_bytes(include_bytes!("../assets/jump.wav"))
-    //    .await
-    //    .unwrap();
+    let snd_bounce = load_sound_from_bytes(include_bytes!("../assets/jump.wav"))
+        .await
+        .unwrap();
     let onebit = Texture2D::from_file_with_format(
         include_bytes!("../assets/kenney/Tilemap/monochrome_tilemap_transparent_packed.png"),
         None,
@@ -147,446 +397,819 @@ async fn main() {
     );
     scavenger.set_filter(FilterMode::Nearest);
 
-    let mut world = World::new();
-    //let start_pos = vec2(200.0, 10000.0);
-    let start_pos = Vec2::ZERO;
-    let player = world.add_actor(start_pos, vec2(32.0, 32.0), 0);
-
-    // The "Level"
-    let level = &mut 32.0;
-    let mut platforms = vec![
-        Platform::new(&mut world, vec2(-50.0, *level), vec2(96.0, 64.0), NO_SLIDE),
-        // Possible drop example
-        Platform::new(
-            &mut world,
-            vec2(250.0, down(level, 317.0)),
-            vec2(192.0, 32.0),
-            NO_SLIDE,
-        ),
-        // Slide example
-        Platform::new(
-            &mut world,
-            vec2(80.0, down(level, 40.0)),
-            vec2(32.0, 304.0),
-            0,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(80.0, down(level, 300.0)),
-            vec2(128.0, 32.0),
-            0,
-        ),
-        // Slide wall jump example
-        Platform::new(
-            &mut world,
-            vec2(400.0, down(level, 40.0)),
-            vec2(32.0, 192.0),
-            0,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(100.0, down(level, 300.0)),
-            vec2(192.0, 32.0),
-            0,
-        ),
-        // No slide example
-        Platform::new(&mut world, vec2(550.0, *level), vec2(32.0, 304.0), NO_SLIDE),
-        Platform::new(
-            &mut world,
-            vec2(232.0, down(level, 268.0)),
-            vec2(320.0, 32.0),
-            NO_SLIDE,
-        ),
-        // Easy steps
-        Platform::new(
-            &mut world,
-            vec2(130.0, down(level, 130.0)),
-            vec2(96.0, 32.0),
-            NO_SLIDE,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(-70.0, down(level, 100.0)),
-            vec2(96.0, 32.0),
-            NO_SLIDE,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(-270.0, down(level, 100.0)),
-            vec2(96.0, 32.0),
-            NO_SLIDE,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(-470.0, down(level, 100.0)),
-            vec2(96.0, 32.0),
-            NO_SLIDE,
-        )
-        .then_moving(vec2(300.0, *level), 300)
-        .then_moving(vec2(-470.0, *level), 200),
-        Platform::new(
-            &mut world,
-            vec2(450.0, down(level, 100.0)),
-            vec2(32.0, 192.0),
-            0,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(150.0, down(level, 400.0)),
-            vec2(128.0, 128.0),
-            0,
-        ),
-        // A few back and forth jumps
-        Platform::new(
-            &mut world,
-            vec2(0.0, down(level, 200.0)),
-            vec2(32.0, 192.0),
-            0,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(200.0, down(level, 200.0)),
-            vec2(32.0, 608.0),
-            0,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(16.0, down(level, 500.0)),
-            vec2(32.0, 192.0),
-            0,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(-50.0, down(level, 500.0)),
-            vec2(192.0, 32.0),
-            0,
-        ),
-        Platform::new(&mut world, vec2(142.0, *level), vec2(64.0, 32.0), NO_SLIDE)
-            .then_pausing(180)
-            .then_moving(vec2(600.0, *level), 300)
-            .then_pausing(180)
-            .then_moving(vec2(142.0, *level), 300),
-        Platform::new(
-            &mut world,
-            vec2(500.0, down(level, 100.0)),
-            vec2(32.0, 192.0),
-            0,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(600.0, down(level, 300.0)),
-            vec2(192.0, 32.0),
-            0,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(800.0, down(level, 200.0)),
-            vec2(96.0, 32.0),
-            NO_SLIDE,
-        )
-        .then_pausing(180)
-        .then_moving(vec2(-400.0, *level), 200)
-        .then_moving(vec2(-700.0, *level - 800.0), 200)
-        .then_pausing(180)
-        .then_moving(vec2(-400.0, *level), 100)
-        .then_moving(vec2(800.0, *level), 100),
-        Platform::new(
-            &mut world,
-            vec2(-732.0, down(level, -800.0)),
-            vec2(32.0, 192.0),
-            NO_SLIDE,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(-968.0, down(level, 300.0)),
-            vec2(128.0, 32.0),
-            NO_SLIDE,
-        ),
-        Platform::new(&mut world, vec2(-732.0, *level), vec2(32.0, 384.0), 0),
-        Platform::new(
-            &mut world,
-            vec2(-792.0, down(level, 368.0)),
-            vec2(64.0, 16.0),
-            DEADLY,
-        ),
-        Platform::new(&mut world, vec2(-700.0, *level), vec2(64.0, 16.0), DEADLY),
-        Platform::new(&mut world, vec2(-968.0, *level), vec2(32.0, 384.0), 0),
-        Platform::new(
-            &mut world,
-            vec2(-968.0, down(level, 600.0)),
-            vec2(128.0, 32.0),
-            0,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(-840.0, down(level, 240.0)),
-            vec2(128.0, 32.0),
-            0,
-        )
-        .then_moving(vec2(-840.0, *level + 300.0), 200)
-        .then_moving(vec2(-840.0, *level), 200),
-        Platform::new(
-            &mut world,
-            vec2(-580.0, down(level, 640.0)),
-            vec2(128.0, 32.0),
-            0,
-        )
-        .then_moving(vec2(-580.0, *level - 200.0), 200)
-        .then_moving(vec2(-580.0, *level), 200),
-        Platform::new(
-            &mut world,
-            vec2(-320.0, down(level, 200.0)),
-            vec2(128.0, 32.0),
-            0,
-        )
-        .then_moving(vec2(-320.0, *level - 600.0), 200)
-        .then_moving(vec2(-320.0, *level), 200),
-        Platform::new(
-            &mut world,
-            vec2(-192.0, down(level, -400.0)),
-            vec2(128.0, 32.0),
-            NO_SLIDE,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(192.0, down(level, 300.0)),
-            vec2(64.0, 32.0),
-            NO_SLIDE,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(256.0, down(level, -600.0)),
-            vec2(64.0, 32.0),
-            NO_SLIDE,
-        )
-        .then_pausing(60)
-        .then_moving(vec2(256.0, *level + 600.0), 200)
-        .then_pausing(60)
-        .then_moving(vec2(256.0, *level), 200),
-        Platform::new(
-            &mut world,
-            vec2(320.0, down(level, 300.0)),
-            vec2(64.0, 32.0),
-            NO_SLIDE,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(600.0, down(level, 300.0)),
-            vec2(32.0, 32.0),
-            NO_SLIDE,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(900.0, down(level, 300.0)),
-            vec2(32.0, 32.0),
-            NO_SLIDE,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(500.0, down(level, 300.0)),
-            vec2(32.0, 32.0),
-            NO_SLIDE,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(200.0, *level - 200.0),
-            vec2(256.0, 32.0),
-            NO_SLIDE,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(184.0, down(level, 300.0)),
-            vec2(64.0, 16.0),
-            DEADLY,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(200.0, down(level, 16.0)),
-            vec2(32.0, 1696.0),
-            0,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(484.0, down(level, 200.0)),
-            vec2(64.0, 16.0),
-            DEADLY,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(500.0, down(level, 16.0)),
-            vec2(32.0, 1696.0),
-            0,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(232.0, *level + 300.0),
-            vec2(64.0, 16.0),
-            DEADLY,
-        )
-        .then_moving(vec2(232.0, *level), 200)
-        .then_moving(vec2(232.0, *level + 300.0), 200),
-        Platform::new(
-            &mut world,
-            vec2(436.0, down(level, 300.0)),
-            vec2(64.0, 16.0),
-            DEADLY,
-        )
-        .then_moving(vec2(436.0, *level + 300.0), 200)
-        .then_moving(vec2(436.0, *level), 200),
-        Platform::new(
-            &mut world,
-            vec2(232.0, down(level, 300.0)),
-            vec2(64.0, 16.0),
-            DEADLY,
-        )
-        .then_moving(vec2(232.0, *level), 200)
-        .then_moving(vec2(232.0, *level + 300.0), 200),
-        Platform::new(
-            &mut world,
-            vec2(436.0, down(level, 300.0)),
-            vec2(64.0, 16.0),
-            DEADLY,
-        )
-        .then_moving(vec2(436.0, *level + 300.0), 200)
-        .then_moving(vec2(436.0, *level), 200),
-        Platform::new(
-            &mut world,
-            vec2(232.0, down(level, 300.0)),
-            vec2(32.0, 16.0),
-            DEADLY,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(232.0, down(level, 268.0)),
-            vec2(96.0, 16.0),
-            NO_SLIDE,
-        ),
-        Platform::new(&mut world, vec2(404.0, *level), vec2(96.0, 16.0), NO_SLIDE),
-        Platform::new(
-            &mut world,
-            vec2(0.0, down(level, 400.0)),
-            vec2(256.0, 16.0),
-            NO_SLIDE,
-        ),
-        Platform::new(&mut world, vec2(320.0, *level), vec2(256.0, 16.0), NO_SLIDE),
-        Platform::new(
-            &mut world,
-            vec2(-32.0, down(level, 200.0)),
-            vec2(256.0, 16.0),
-            NO_SLIDE,
-        ),
-        Platform::new(&mut world, vec2(288.0, *level), vec2(256.0, 16.0), NO_SLIDE),
-        Platform::new(
-            &mut world,
-            vec2(-16.0, down(level, 250.0)),
-            vec2(256.0, 16.0),
-            NO_SLIDE,
-        ),
-        Platform::new(&mut world, vec2(306.0, *level), vec2(256.0, 16.0), NO_SLIDE),
-        Platform::new(
-            &mut world,
-            vec2(350.0, down(level, 200.0)),
-            vec2(32.0, 1008.0),
-            0,
-        ),
-        // DUMMY
-        Platform::new(
-            &mut world,
-            vec2(256.0, down(level, 1000.0)),
-            vec2(304.0, 32.0),
-            NO_SLIDE,
-        ),
-        // GROUND
-        Platform::new(
-            &mut world,
-            vec2(-1000.0, down(level, 300.0)),
-            vec2(2000.0, 32.0),
-            GROUND_LEVEL | NO_SLIDE,
-        ),
-        Platform::new(
-            &mut world,
-            vec2(-1000.0, 0.0),
-            vec2(32.0, down(level, 15.0)),
-            NO_SLIDE,
-        ),
-        Platform::new(&mut world, vec2(1000.0, 0.0), vec2(32.0, *level), NO_SLIDE),
-    ];
-    for platform in platforms.iter() {
-        if world.solid_has_flag(platform.solid, DEADLY) {
-            continue;
-        }
-        if gen_range(0.0, 1.0) > 0.7 {
-            let collider = world.solid_collider(platform.solid);
-            if collider.dimension.x >= 32.0 {
-                let x = gen_range(16.0, collider.dimension.x - 16.0) + collider.position.x - 16.0;
-                world.add_actor(
-                    vec2(x, collider.position.y - 32.0),
-                    vec2(32.0, 32.0),
-                    COIN | NOT_TAKEN,
-                );
-            }
-        }
-    }
-    let mut dx = 0.0;
-    let mut dy = 1.0;
+    // Levels with their diamond totals, for the level select
+    let campaign: Vec<(LevelDesc, usize)> = LEVELS
+        .iter()
+        .map(|source| {
+            let level = LevelDesc::parse(source).unwrap();
+            let diamonds = level.diamonds(0);
+            (level, diamonds)
+        })
+        .collect();
+    let mut progress = Progress::load();
+    let mut level_select: Option<usize> = None;
+    // Generated towers from the command line are built like any other
+    let mut selected_tower = Some(Tower::from_args());
+    let mut tower = Tower::Level(0);
+    let (mut level, mut world, mut platforms) = tower.load(None);
+    // A tower that is still being built, the game waits until its builder is done
+    let mut building: Option<(Build, TowerBuilder)> = None;
+    // Diamonds of the daily towers built so far, checking results does not build them again
+    let mut daily_diamonds: Vec<(u64, usize)> = Vec::new();
+    let mut groups = level.groups.clone();
+    let mut switches = level.build_switches(&mut world);
+    let mut enemies = level.build_enemies(&mut world);
+    let mut rockfalls = level.build_rockfalls();
+    let mut pickups = level.build_pickups(&mut world);
+    let mut channels = level.channels.clone();
+    // Switches touched during the last step, levers only flip on touching them again
+    let mut inside_switches: Vec<bool> = Vec::new();
+    // Sensors the scavenger was in during the last step, signals only fire on entering
+    let mut inside_sensors: Vec<bool> = Vec::new();
+    // Water or hay the scavenger was in last step, to splash when entering
+    let mut medium: Option<Medium> = None;
+    let mut player = Scavenger::new(&mut world, level.start);
+    player.abilities = level.abilities;
+    player.health = level.health.map(Health::new);
     let mut delta = 1.0 / 60.0;
     let mut camera_target = Vec2::ZERO;
     let mut s_anim_index = 0;
     let mut s_anim_time = 8;
     let mut s_anim;
     let mut timer = 0;
+    // Whether assists were on at any point of the current run
+    let mut assisted = false;
+    // Mode picked in the level select, and the one of the current run
+    let mut run_mode = RunMode::Normal;
+    let mut mode = run_mode;
+    // Same for the health mode, levels asking for it always have it
+    let mut run_health = false;
+    let mut health_mode = run_health;
+    let mut lives = START_LIVES;
+    // Results of a run that ended by dying, shown until it is restarted
+    let mut run_over: Option<String> = None;
     let mut coins = 0;
     let mut game_ended = false;
+    let mut checkpoint: Option<Checkpoint> = None;
+    // Deaths after the start and after each checkpoint
+    let mut deaths: Vec<u32> = Vec::new();
+    // Practice runs can start at any soul-stone and never count
+    let mut practice = false;
+    let mut practice_start = 0;
+    let mut save_state: Option<SaveState> = None;
+    let mut history = History::load();
+    let mut show_history = false;
+    let mut message = String::new();
+    let mut tuning_file = TuningFile::load();
+    // Selected value of the tuning panel while it is open
+    let mut tuning_panel: Option<usize> = None;
+    let mut assists = Assists::default();
+    // Selected row of the assist menu while it is open, the game is paused meanwhile
+    let mut assist_menu: Option<usize> = None;
+    player.config = controller_config(&level, &tuning_file, &mut message);
+    // The bounce pad last landed on and for how many more steps it is pressed in
+    let mut squash: Option<(Solid, u32)> = None;
     let mut gamepads = Gamepads::new();
     loop {
         clear_background(BLACK);
 
+        if let Some(selected) = &mut level_select {
+            if is_key_pressed(KeyCode::Up) {
+                *selected = selected.checked_sub(1).unwrap_or(campaign.len() - 1);
+            }
+            if is_key_pressed(KeyCode::Down) {
+                *selected = (*selected + 1) % campaign.len();
+            }
+            if is_key_pressed(KeyCode::Left) {
+                run_mode = run_mode.cycle(-1);
+            }
+            if is_key_pressed(KeyCode::Right) {
+                run_mode = run_mode.cycle(1);
+            }
+            if is_key_pressed(KeyCode::H) {
+                run_health = !run_health;
+            }
+            let chosen = *selected;
+            let playable = progress.unlocked(&campaign[chosen].0);
+            if is_key_pressed(KeyCode::Escape) || is_key_pressed(KeyCode::F1) {
+                level_select = None;
+            } else if is_key_pressed(KeyCode::Enter) && playable {
+                level_select = None;
+                selected_tower = Some(Tower::Level(chosen));
+            }
+
+            draw_text("Select a tower", 40.0, 60.0, 40.0, WHITE);
+            for (index, (entry, total)) in campaign.iter().enumerate() {
+                let y = 120.0 + index as f32 * 90.0;
+                let marker = if index == chosen { ">" } else { " " };
+                let author = if entry.author.is_empty() {
+                    String::new()
+                } else {
+                    format!("  by {}", entry.author)
+                };
+                draw_text(
+                    &format!("{} {}{}", marker, entry.name, author),
+                    40.0,
+                    y,
+                    30.0,
+                    WHITE,
+                );
+                let details = if progress.unlocked(entry) {
+                    let par = entry.par.map_or(String::new(), |par| {
+                        format!("Par {}  ", daily::format_time(par))
+                    });
+                    // Records of the mode a run would be started in
+                    match progress.record(&entry.name, run_mode) {
+                        Some(record) => format!(
+                            "{}Best {}  Diamonds {}/{}{}",
+                            par,
+                            daily::format_time(record.time),
+                            record.diamonds,
+                            total,
+                            if record.assisted { "  (assisted)" } else { "" },
+                        ),
+                        None if progress.finished(&entry.name) => {
+                            format!("{}Not finished in this mode  Diamonds 0/{}", par, total)
+                        }
+                        None => format!("{}Not finished  Diamonds 0/{}", par, total),
+                    }
+                } else {
+                    let missing: Vec<String> = entry
+                        .unlocks
+                        .iter()
+                        .map(|unlock| requirement(unlock, &progress))
+                        .collect();
+                    format!("Locked: {}", missing.join(", "))
+                };
+                draw_text(&details, 72.0, y + 32.0, 24.0, WHITE);
+            }
+            draw_text(
+                &format!(
+                    "Mode: {}  Health: {}",
+                    run_mode.label(),
+                    if run_health { "on" } else { "off" }
+                ),
+                40.0,
+                screen_height() - 70.0,
+                24.0,
+                WHITE,
+            );
+            draw_text(
+                "Up/Down to choose, Left/Right to change the mode, H for health, Enter to play, Escape to go back",
+                40.0,
+                screen_height() - 40.0,
+                24.0,
+                WHITE,
+            );
+            next_frame().await;
+            continue;
+        }
+
+        let mut built = None;
+        if let Some((build, builder)) = &mut building {
+            built = builder.work(BUILD_STATES_PER_FRAME);
+            let day = match build {
+                Build::Play(Tower::Daily { day, .. }) => Some(*day),
+                Build::Check(result) => Some(result.day),
+                Build::Play(_) => None,
+            };
+            if let (Some(day), Some(level)) = (day, &built) {
+                daily_diamonds.push((day, level.diamonds(0)));
+            }
+            if let (Build::Check(result), Some(level)) = (&*build, &built) {
+                let checked = result.plausible(level.diamonds(0)).map(|()| *result);
+                message = result_message(&result.code(), checked);
+                built = None;
+                building = None;
+            } else if built.is_none() {
+                let doing = match build {
+                    Build::Play(_) => "Generating tower",
+                    Build::Check(_) => "Checking result",
+                };
+                draw_text(
+                    &format!("{}... {}%", doing, (builder.progress() * 100.0) as u32),
+                    0.0,
+                    28.0,
+                    30.0,
+                    WHITE,
+                );
+                draw_text("Escape to cancel", 0.0, 56.0, 24.0, WHITE);
+                if is_key_pressed(KeyCode::Escape) {
+                    building = None;
+                }
+                next_frame().await;
+                continue;
+            }
+        }
+
+        if is_key_pressed(KeyCode::F1) {
+            level_select = Some(match tower {
+                Tower::Level(index) => index,
+                _ => 0,
+            });
+            // F1 is still pressed this frame, and would close the level select again
+            next_frame().await;
+            continue;
+        }
+        let next_tower = if built.is_some() {
+            match building.take() {
+                Some((Build::Play(tower), _)) => Some(tower),
+                _ => None,
+            }
+        } else if selected_tower.is_some() {
+            selected_tower.take()
+        } else if is_key_pressed(KeyCode::Enter) && game_ended {
+            // Finished campaign levels lead on to the next one
+            match tower {
+                Tower::Level(index)
+                    if index + 1 < campaign.len() && progress.unlocked(&campaign[index + 1].0) =>
+                {
+                    Some(Tower::Level(index + 1))
+                }
+                Tower::Level(index) => {
+                    level_select = Some(index);
+                    None
+                }
+                _ => None,
+            }
+        } else if is_key_pressed(KeyCode::F2) {
+            Some(Tower::Random(random_seed()))
+        } else if is_key_pressed(KeyCode::F3) {
+            Some(Tower::endless(random_seed()))
+        } else if is_key_pressed(KeyCode::F4) {
+            let day = daily::today();
+            let scored = !history.attempted(day);
+            Some(Tower::Daily { day, scored })
+        } else {
+            None
+        };
+        // Towers with a builder are started once it is done
+        let next_tower = next_tower.and_then(|next_tower| match next_tower.builder() {
+            Some(builder) if built.is_none() => {
+                building = Some((Build::Play(next_tower), builder));
+                None
+            }
+            _ => Some(next_tower),
+        });
+        if let Some(next_tower) = next_tower {
+            if let Tower::Daily { day, scored: true } = next_tower {
+                // Only once the tower is there, cancelling it does not use up the attempt
+                history.start(day);
+            }
+            tower = next_tower;
+            (level, world, platforms) = tower.load(built);
+            groups = level.groups.clone();
+            switches = level.build_switches(&mut world);
+            enemies = level.build_enemies(&mut world);
+            rockfalls = level.build_rockfalls();
+            pickups = level.build_pickups(&mut world);
+            channels = level.channels.clone();
+            player = Scavenger::new(&mut world, level.start);
+            player.abilities = level.abilities;
+            health_mode = run_health;
+            player.health = start_health(&level, health_mode);
+            mode = run_mode;
+            lives = START_LIVES;
+            run_over = None;
+            timer = 0;
+            assisted = false;
+            coins = 0;
+            game_ended = false;
+            checkpoint = None;
+            deaths.clear();
+            practice = false;
+            practice_start = 0;
+            save_state = None;
+            message.clear();
+            player.config = controller_config(&level, &tuning_file, &mut message);
+        }
+        if is_key_pressed(KeyCode::F5) && building.is_none() {
+            let code = clipboard_get().unwrap_or_default();
+            match DailyResult::from_code(&code)
+                .and_then(|result| result.released().map(|()| result))
+            {
+                Ok(result) => match daily_diamonds.iter().find(|(day, _)| *day == result.day) {
+                    Some(&(_, available)) => {
+                        let checked = result.plausible(available).map(|()| result);
+                        message = result_message(&code, checked);
+                    }
+                    // Checking generates the tower of the day
+                    None => building = Some((Build::Check(result), daily::builder(result.day))),
+                },
+                Err(err) => message = result_message(&code, Err(err)),
+            }
+        }
+        if is_key_pressed(KeyCode::F6) {
+            show_history = !show_history;
+        }
+        if tuning_file.reload(get_time()) {
+            message = format!("Reloaded {}", TUNING_FILE);
+            player.config = controller_config(&level, &tuning_file, &mut message);
+        }
+        // Not F11 and F12 like the other tools, browsers keep those for fullscreen and devtools
+        if is_key_pressed(KeyCode::GraveAccent) {
+            tuning_panel = match tuning_panel {
+                Some(_) => None,
+                None => Some(0),
+            };
+        }
+        if is_key_pressed(KeyCode::Tab) {
+            assist_menu = match assist_menu {
+                Some(_) => None,
+                None => Some(0),
+            };
+        }
+        if let Some(selected) = &mut assist_menu {
+            let rows = assist_lines(&assists).len();
+            if is_key_pressed(KeyCode::Up) {
+                *selected = selected.checked_sub(1).unwrap_or(rows - 1);
+            }
+            if is_key_pressed(KeyCode::Down) {
+                *selected = (*selected + 1) % rows;
+            }
+            let change =
+                is_key_pressed(KeyCode::Right) as i32 - is_key_pressed(KeyCode::Left) as i32;
+            if change != 0 {
+                match *selected {
+                    0 => {
+                        let tenths = (assists.speed * 10.0).round() as i32 + change;
+                        assists.speed = tenths.clamp(7, 10) as f32 / 10.0;
+                    }
+                    1 => assists.safe_drop_line = !assists.safe_drop_line,
+                    2 => assists.deadly_immunity = !assists.deadly_immunity,
+                    3 => assists.fall_immunity = !assists.fall_immunity,
+                    _ => assists.slide_everywhere = !assists.slide_everywhere,
+                }
+            }
+        }
+        if let Some(selected) = &mut tuning_panel {
+            if is_key_pressed(KeyCode::PageUp) {
+                *selected = selected.checked_sub(1).unwrap_or(TUNING.len() - 1);
+            }
+            if is_key_pressed(KeyCode::PageDown) {
+                *selected = (*selected + 1) % TUNING.len();
+            }
+            let (name, nudge, _) = TUNING[*selected];
+            let value = player.config.get(name).unwrap_or_default();
+            if is_key_pressed(KeyCode::LeftBracket) {
+                player.config.set(name, value - nudge);
+            }
+            if is_key_pressed(KeyCode::RightBracket) {
+                player.config.set(name, value + nudge);
+            }
+            if is_key_pressed(KeyCode::Backslash) {
+                tuning_file.save(&player.config);
+                message = format!("Saved {}", TUNING_FILE);
+            }
+        }
+        let mut restart = false;
+        if is_key_pressed(KeyCode::F7) {
+            practice = !practice;
+            practice_start = 0;
+            save_state = None;
+            restart = true;
+            if practice {
+                if let Tower::Daily { scored, .. } = &mut tower {
+                    *scored = false;
+                }
+                message = "Practice: F8 next soul-stone, F9 save state, F10 load state".to_owned();
+            } else {
+                message.clear();
+            }
+        }
+        if run_over.is_some() && is_key_pressed(KeyCode::Enter) {
+            restart = true;
+        }
+        if practice && is_key_pressed(KeyCode::F8) {
+            practice_start = (practice_start + 1) % (level.checkpoints.len() + 1);
+            restart = true;
+        }
+        if restart {
+            for platform in platforms.iter_mut() {
+                platform.reset(&mut world);
+            }
+            for group in groups.iter_mut() {
+                group.reset();
+            }
+            reset_taken(&mut world, COIN, &[]);
+            reset_taken(&mut world, KEY, &[]);
+            channels = level.channels.clone();
+            for enemy in enemies.iter_mut() {
+                enemy.reset(&mut world);
+            }
+            for rockfall in rockfalls.iter_mut() {
+                rockfall.reset(&mut world);
+            }
+            checkpoint = practice_start.checked_sub(1).map(|index| Checkpoint {
+                index,
+                position: level.checkpoints[index],
+                coins: 0,
+                taken: Vec::new(),
+                keys: Vec::new(),
+                channels: level.channels.clone(),
+            });
+            let respawn = checkpoint
+                .as_ref()
+                .map_or(level.start, |stone| stone.position);
+            world.set_actor_pos(player.actor, respawn);
+            player.dx = 0.0;
+            player.dy = 1.0;
+            player.abilities = level.abilities;
+            player.charges = Charges::default();
+            player.grace = Grace::default();
+            health_mode = run_health;
+            player.health = start_health(&level, health_mode);
+            reset_pickups(&mut world, &pickups, player.abilities, true);
+            mode = run_mode;
+            lives = START_LIVES;
+            run_over = None;
+            timer = 0;
+            assisted = false;
+            coins = 0;
+            game_ended = false;
+            deaths.clear();
+        }
+        if is_key_pressed(KeyCode::F9) {
+            if practice {
+                save_state = Some(SaveState {
+                    position: world.actor_pos(player.actor),
+                    dx: player.dx,
+                    dy: player.dy,
+                    climbing: player.climbing,
+                    abilities: player.abilities,
+                    charges: player.charges,
+                    grace: player.grace,
+                    health: player.health,
+                    lives,
+                    timer,
+                    coins,
+                    taken: taken(&world, COIN),
+                    platforms: platforms
+                        .iter()
+                        .map(|platform| platform.state(&world))
+                        .collect(),
+                    groups: groups.clone(),
+                    keys: taken(&world, KEY),
+                    channels: channels.clone(),
+                });
+                message = "State saved".to_owned();
+            } else {
+                message = "Save states only work in practice mode (F7)".to_owned();
+            }
+        }
+        if is_key_pressed(KeyCode::F10) {
+            if let (true, Some(state)) = (practice, &save_state) {
+                world.set_actor_pos(player.actor, state.position);
+                player.dx = state.dx;
+                player.dy = state.dy;
+                player.climbing = state.climbing;
+                player.abilities = state.abilities;
+                player.charges = state.charges;
+                player.grace = state.grace;
+                player.health = state.health;
+                reset_pickups(&mut world, &pickups, player.abilities, false);
+                lives = state.lives;
+                timer = state.timer;
+                coins = state.coins;
+                reset_taken(&mut world, COIN, &state.taken);
+                for (platform, platform_state) in platforms.iter_mut().zip(&state.platforms) {
+                    platform.restore(&mut world, platform_state);
+                }
+                groups = state.groups.clone();
+                reset_taken(&mut world, KEY, &state.keys);
+                channels = state.channels.clone();
+                // Enemies and rocks are not part of save states, they start over
+                for enemy in enemies.iter_mut() {
+                    enemy.reset(&mut world);
+                }
+                for rockfall in rockfalls.iter_mut() {
+                    rockfall.reset(&mut world);
+                }
+                game_ended = false;
+            }
+        }
+
         world.step_particles();
-        let mut pos = world.actor_pos(player);
-        delta += get_frame_time();
+        let mut pos = world.actor_pos(player.actor);
+        delta += get_frame_time() * assists.speed;
+        if assist_menu.is_some() || run_over.is_some() {
+            delta = 0.0;
+        }
+        player.assists = assists;
+        // Tuning the controls away from the preset of the level counts as an assist too, so
+        // does the health mode in levels that do not ask for it
+        let tuned = player.config != level.controller;
+        let hurt_only = health_mode && level.health.is_none();
+        if (assists.any() || tuned || hurt_only) && !game_ended {
+            assisted = true;
+            if let Tower::Daily { scored, .. } = &mut tower {
+                if *scored {
+                    *scored = false;
+                    message = if tuned {
+                        "Controls are tuned, practice from here on"
+                    } else if hurt_only {
+                        "Health mode is on, practice from here on"
+                    } else {
+                        "Assists are on, practice from here on"
+                    }
+                    .to_owned();
+                }
+            }
+        }
+        if let Tower::Daily { scored, .. } = &mut tower {
+            if *scored && mode == RunMode::NoReset {
+                *scored = false;
+                message = "Diamonds are kept through deaths, practice only".to_owned();
+            }
+        }
         gamepads.poll();
+        // Jumps and dashes happen once per press, in the first step of the frame
+        let mut jump_pressed = is_key_pressed(KeyCode::Space)
+            || gamepads
+                .all()
+                .any(|gamepad| gamepad.is_just_pressed(Button::ActionDown));
+        let mut dash_pressed = is_key_pressed(KeyCode::LeftShift)
+            || is_key_pressed(KeyCode::RightShift)
+            || gamepads
+                .all()
+                .any(|gamepad| gamepad.is_just_pressed(Button::ActionLeft));
         while delta > 0.9 / 60.0 {
             delta -= 1.0 / 60.0;
-            let (wall_candidate, coin_candidate) = world.move_h(player, dx);
-            if wall_candidate.is_some() {
-                dx = 0.0;
-            }
-            if let Some(coin_candidate) = coin_candidate {
+            let input = Input {
+                right: is_key_down(KeyCode::Right)
+                    || is_key_down(KeyCode::D)
+                    || mouse_position_local().x > 0.0 && is_mouse_button_down(MouseButton::Left)
+                    || gamepads.all().any(|gamepad| {
+                        gamepad.left_stick_x() > 0.2
+                            || gamepad.is_currently_pressed(Button::DPadRight)
+                    }),
+                left: is_key_down(KeyCode::Left)
+                    || is_key_down(KeyCode::A)
+                    || mouse_position_local().x < 0.0 && is_mouse_button_down(MouseButton::Left)
+                    || gamepads.all().any(|gamepad| {
+                        gamepad.left_stick_x() < -0.2
+                            || gamepad.is_currently_pressed(Button::DPadLeft)
+                    }),
+                up: is_key_down(KeyCode::Up)
+                    || is_key_down(KeyCode::W)
+                    || gamepads.all().any(|gamepad| {
+                        gamepad.left_stick_y() > 0.5 || gamepad.is_currently_pressed(Button::DPadUp)
+                    }),
+                down: is_key_down(KeyCode::Down)
+                    || is_key_down(KeyCode::S)
+                    || gamepads.all().any(|gamepad| {
+                        gamepad.left_stick_y() < -0.5
+                            || gamepad.is_currently_pressed(Button::DPadDown)
+                    }),
+                jump: std::mem::take(&mut jump_pressed),
+                dash: std::mem::take(&mut dash_pressed),
+                glide: is_key_down(KeyCode::Space)
+                    || gamepads
+                        .all()
+                        .any(|gamepad| gamepad.is_currently_pressed(Button::ActionDown)),
+            };
+            let step = player.step(&mut world, input);
+            for coin_candidate in step.touched.into_iter().flatten() {
                 if world.actor_has_flag(coin_candidate, COIN | NOT_TAKEN) {
                     world.actor_unset_flag(coin_candidate, NOT_TAKEN);
                     coins += 1;
                     sfx(&snd_pickup);
                 }
             }
-            let (floor, coin_candidate) = world.move_v(player, dy);
-            if let Some(coin_candidate) = coin_candidate {
-                if world.actor_has_flag(coin_candidate, COIN | NOT_TAKEN) {
-                    world.actor_unset_flag(coin_candidate, NOT_TAKEN);
-                    coins += 1;
+            if step.jumped {
+                sfx(&snd_bounce);
+            }
+            if step.dashed {
+                sfx(&snd_step);
+            }
+            pos = world.actor_pos(player.actor);
+            let body = Rect::new(pos.x, pos.y, 32.0, 32.0);
+            for (index, stone) in level.checkpoints.iter().enumerate() {
+                // Climbing back up to the active stone or an earlier one does not go back a segment
+                let passed = checkpoint
+                    .as_ref()
+                    .is_some_and(|active| active.index >= index);
+                if !passed && !step.died && soul_stone_rect(*stone).overlaps(&body) {
+                    checkpoint = Some(Checkpoint {
+                        index,
+                        position: *stone,
+                        coins,
+                        taken: taken(&world, COIN),
+                        keys: taken(&world, KEY),
+                        channels: channels.clone(),
+                    });
                     sfx(&snd_pickup);
+                    for _ in 0..20 {
+                        world.add_particle(
+                            *stone - vec2(24.0, 0.0),
+                            vec2(gen_range(-1.0, 1.0), gen_range(-3.0, -1.0)),
+                        );
+                    }
+                }
+            }
+            if step.medium.is_some() && medium.is_none() {
+                sfx(&snd_land);
+                for _ in 0..30 {
+                    world.add_particle(
+                        pos + vec2(16.0, 16.0),
+                        vec2(gen_range(-2.0, 2.0), gen_range(-5.0, -1.0)),
+                    );
+                }
+            }
+            medium = step.medium;
+            for pickup in pickups.iter() {
+                let pickup_pos = world.actor_pos(pickup.actor);
+                let usable = pickup.kind != PickupKind::Life || mode == RunMode::Lives;
+                if usable
+                    && world.actor_has_flag(pickup.actor, NOT_TAKEN)
+                    && !step.died
+                    && Rect::new(pickup_pos.x, pickup_pos.y, 32.0, 32.0).overlaps(&body)
+                {
+                    world.actor_unset_flag(pickup.actor, NOT_TAKEN);
+                    sfx(&snd_pickup);
+                    match pickup.kind {
+                        PickupKind::Ability(ability) => {
+                            player.abilities.grant(ability);
+                            message = match ability {
+                                Ability::Dash => "Learned to dash: Shift in mid-air",
+                                Ability::DoubleJump => "Learned to double jump: Space in mid-air",
+                                Ability::Glide => "Learned to glide: hold Space while falling",
+                            }
+                            .to_owned();
+                        }
+                        PickupKind::Heal(amount) => {
+                            if let Some(health) = &mut player.health {
+                                health.heal(amount);
+                            }
+                        }
+                        PickupKind::Life => lives += 1,
+                    }
+                }
+            }
+            inside_switches.resize(switches.len(), false);
+            for (switch, inside) in switches.iter().zip(inside_switches.iter_mut()) {
+                let switch_pos = world.actor_pos(switch.actor);
+                let touching =
+                    !step.died && Rect::new(switch_pos.x, switch_pos.y, 32.0, 32.0).overlaps(&body);
+                let channel = &mut channels[switch.channel];
+                if world.actor_has_flag(switch.actor, KEY | NOT_TAKEN) && touching {
+                    world.actor_unset_flag(switch.actor, NOT_TAKEN);
+                    channel.on = true;
+                    sfx(&snd_pickup);
+                } else if world.actor_has_flag(switch.actor, LEVER) && touching && !*inside {
+                    channel.on = !channel.on;
+                    sfx(&snd_step);
+                } else if world.actor_has_flag(switch.actor, PLATE) && touching != *inside {
+                    // Plates only stay on while stood on
+                    channel.on = touching;
+                    sfx(&snd_step);
+                }
+                *inside = touching;
+            }
+            inside_sensors.resize(level.sensors.len(), false);
+            for (sensor, inside) in level.sensors.iter().zip(inside_sensors.iter_mut()) {
+                let entered = sensor.rect.overlaps(&body);
+                if entered && !*inside {
+                    fire(&mut world, &mut platforms, &mut groups, &sensor.name);
                 }
+                *inside = entered;
             }
-            pos = world.actor_pos(player);
-            let mut control = 0.5;
-            if let Some(floor) = floor {
-                if dy > 8.0 || world.solid_has_flag(floor, DEADLY) {
-                    dx = 0.0;
-                    world.set_actor_pos(player, start_pos);
+            if step.hurt {
+                sfx(&snd_land);
+            }
+            // Enemies move after the scavenger, touching them hurts or throws it back
+            let mut killed = false;
+            for enemy in enemies.iter_mut() {
+                enemy.step(&mut world, pos);
+                if step.died || killed || !enemy.touches(&world, body) {
+                    continue;
+                }
+                match enemy.contact {
+                    Contact::Kill => {
+                        let health = player.health;
+                        if player.hurt(HAZARD_DAMAGE) {
+                            killed = true;
+                            player.dx = 0.0;
+                            player.dy = 0.0;
+                        } else if player.health != health {
+                            sfx(&snd_land);
+                        }
+                    }
+                    Contact::KnockBack => {
+                        let away = if pos.x < world.actor_pos(enemy.actor).x {
+                            -1.0
+                        } else {
+                            1.0
+                        };
+                        player.dx = away * 5.0;
+                        player.dy = -3.0;
+                        player.climbing = false;
+                        sfx(&snd_land);
+                    }
+                }
+            }
+            for rockfall in rockfalls.iter_mut() {
+                let rocks = rockfall.step(&mut world);
+                if rocks.warning && gen_range(0.0, 1.0) > 0.5 {
+                    world.add_particle(
+                        rockfall.desc.position + vec2(gen_range(-8.0, 8.0), 0.0),
+                        vec2(gen_range(-0.3, 0.3), 0.0),
+                    );
+                }
+                for position in rocks.broken {
+                    sfx(&snd_die);
+                    for _ in 0..15 {
+                        world.add_particle(
+                            position,
+                            vec2(gen_range(-2.0, 2.0), gen_range(-3.0, 0.0)),
+                        );
+                    }
+                }
+                if !step.died && !killed && rockfall.touches(&world, body) {
+                    let health = player.health;
+                    if player.hurt(HAZARD_DAMAGE) {
+                        killed = true;
+                        player.dx = 0.0;
+                        player.dy = 0.0;
+                    } else if player.health != health {
+                        sfx(&snd_land);
+                    }
+                }
+            }
+            if step.died || killed {
+                let out = match mode {
+                    RunMode::Lives => {
+                        lives = lives.saturating_sub(1);
+                        lives == 0
+                    }
+                    RunMode::Hardcore => true,
+                    RunMode::Normal | RunMode::NoReset => false,
+                };
+                if out {
+                    run_over = Some(format!(
+                        "Run over  {}  Time {}  Diamonds {}",
+                        height_text(&tower, &level, pos),
+                        daily::format_time(timer),
+                        coins
+                    ));
+                }
+                let respawn = checkpoint
+                    .as_ref()
+                    .map_or(level.start, |stone| stone.position);
+                world.set_actor_pos(player.actor, respawn);
+                player.climbing = false;
+                player.grace = Grace::default();
+                player.health = start_health(&level, health_mode);
+                reset_pickups(&mut world, &pickups, player.abilities, false);
+                for enemy in enemies.iter_mut() {
+                    enemy.reset(&mut world);
+                }
+                for rockfall in rockfalls.iter_mut() {
+                    rockfall.reset(&mut world);
+                }
+                if checkpoint.is_none() || !level.keep_platforms {
                     for platform in platforms.iter_mut() {
                         platform.reset(&mut world);
                     }
-                    let coin_actors: Vec<_> = world
-                        .actors()
-                        .filter(|(_, collider)| collider.flags & COIN != 0)
-                        .map(|(coin, _)| coin)
-                        .collect();
-                    for coin in coin_actors {
-                        world.actor_set_flag(coin, NOT_TAKEN);
+                    for group in groups.iter_mut() {
+                        group.reset();
                     }
+                }
+                let keep_diamonds = checkpoint.is_some() && level.keep_diamonds;
+                if mode != RunMode::NoReset && !keep_diamonds {
+                    let taken = checkpoint.as_ref().map_or(&[][..], |stone| &stone.taken);
+                    reset_taken(&mut world, COIN, taken);
+                    coins = checkpoint.as_ref().map_or(0, |stone| stone.coins);
+                }
+                if checkpoint.is_none() {
                     timer = 0;
-                    coins = 0;
-                    sfx(&snd_die);
-                } else if dy > 6.0 / 60.0 {
+                    assisted = false;
+                }
+                // Switches always go back to how they were at the soul-stone
+                let keys = checkpoint.as_ref().map_or(&[][..], |stone| &stone.keys);
+                reset_taken(&mut world, KEY, keys);
+                channels = checkpoint
+                    .as_ref()
+                    .map_or(&level.channels, |stone| &stone.channels)
+                    .clone();
+                inside_switches.clear();
+                let segment = checkpoint.as_ref().map_or(0, |stone| stone.index + 1);
+                if deaths.len() <= segment {
+                    deaths.resize(segment + 1, 0);
+                }
+                deaths[segment] += 1;
+                sfx(&snd_die);
+                if let Tower::Daily { scored, .. } = &mut tower {
+                    if *scored {
+                        *scored = false;
+                        message = "Daily attempt failed, practice from here on".to_owned();
+                    }
+                }
+            } else if let Some(floor) = step.floor {
+                if step.bounced {
+                    sfx(&snd_bounce);
+                    squash = Some((floor, SQUASH_STEPS));
+                } else if step.impact > player.config.landing_impact {
                     sfx(&snd_land);
                     for _ in 0..20 {
                         world.add_particle(
@@ -595,12 +1218,39 @@ async fn main() {
                         );
                     }
                 } else if !world.solid_has_flag(floor, GROUND_LEVEL) {
-                    if pos != Vec2::ZERO {
+                    if pos != level.start {
                         timer += 1;
                     }
                 } else {
                     if !game_ended {
                         sfx(&snd_wise_crack);
+                        match &mut tower {
+                            Tower::Level(_) if practice => {
+                                message = "Practice run, not recorded".to_owned();
+                            }
+                            Tower::Level(index) => {
+                                progress.finish(&level.name, timer, coins, assisted, mode);
+                                let next = campaign.get(*index + 1).map(|(next, _)| next);
+                                message = match next {
+                                    Some(next) if progress.unlocked(next) => {
+                                        format!("Press Enter for {}", next.name)
+                                    }
+                                    _ => "Press Enter for the level select".to_owned(),
+                                };
+                            }
+                            Tower::Daily { day, scored } if *scored => {
+                                *scored = false;
+                                let result = DailyResult {
+                                    day: *day,
+                                    time: timer,
+                                    diamonds: coins,
+                                };
+                                history.finish(result);
+                                clipboard_set(&result.code());
+                                message = format!("{}, copied {}", result, result.code());
+                            }
+                            _ => {}
+                        }
                     }
                     game_ended = true;
                     for _ in 0..3 {
@@ -610,58 +1260,11 @@ async fn main() {
                         );
                     }
                 }
-                dy = 0.0;
-                control = 1.0;
             } else {
                 timer += 1;
             }
-            let wall = floor
-                .is_none()
-                .then_some(
-                    world
-                        .collide_solids(pos + vec2(0.0, 0.05), vec2(32.0, 32.0 - 0.1))
-                        .filter(|(solid, _)| !world.solid_has_flag(*solid, NO_SLIDE)),
-                )
-                .flatten();
-
-            if is_key_down(KeyCode::Right)
-                || is_key_down(KeyCode::D)
-                || mouse_position_local().x > 0.0 && is_mouse_button_down(MouseButton::Left)
-                || gamepads.all().any(|gamepad| {
-                    gamepad.left_stick_x() > 0.2 || gamepad.is_currently_pressed(Button::DPadRight)
-                })
-            {
-                if wall.is_some() {
-                    dx = 5.0;
-                    //sfx(&snd_jump);
-                } else {
-                    dx = (dx + control * 8.0 / 60.0).min(5.0);
-                }
-            } else if is_key_down(KeyCode::Left)
-                || is_key_down(KeyCode::A)
-                || mouse_position_local().x < 0.0 && is_mouse_button_down(MouseButton::Left)
-                || gamepads.all().any(|gamepad| {
-                    gamepad.left_stick_x() < -0.2 || gamepad.is_currently_pressed(Button::DPadLeft)
-                })
-            {
-                if wall.is_some() {
-                    dx = -5.0;
-                    //sfx(&snd_jump);
-                } else {
-                    dx = (dx - control * 8.0 / 60.0).max(-5.0);
-                }
-            } else if dx > 0.0 {
-                dx = (dx - control * 16.0 / 60.0).max(0.0);
-            } else if dx < 0.0 {
-                dx = (dx + control * 16.0 / 60.0).min(0.0);
-            }
 
-            if let Some((_, rect)) = wall {
-                if dy > 0.0 {
-                    dy = (dy - 32.0 / 60.0).max(2.0);
-                } else {
-                    dy = (dy + 32.0 / 60.0).min(2.0);
-                }
+            if let Some((_, rect)) = step.wall {
                 let dv = vec2(gen_range(0.0, 2.0), 0.0);
                 let wall_pos = vec2(rect.x, gen_range(rect.top(), rect.bottom()));
                 if pos.x > wall_pos.x {
@@ -671,41 +1274,27 @@ async fn main() {
                 }
             }
 
-            if dy != 0.0 {
+            if player.climbing {
+                s_anim = ScavengerAnim::Climb;
+            } else if player.charges.dash_timer > 0 {
+                s_anim = ScavengerAnim::Dash;
+            } else if player.gliding {
+                s_anim = ScavengerAnim::Glide;
+            } else if step.floor.is_none() && player.dy < 0.0 {
+                s_anim = ScavengerAnim::Jump;
+            } else if step.floor.is_none() {
                 s_anim = ScavengerAnim::Fall;
-            } else if dx != 0.0 {
+            } else if player.dx != 0.0 {
                 s_anim = ScavengerAnim::Run;
             } else {
                 s_anim = ScavengerAnim::Idle;
             }
 
-            dy += GRAVITY.y;
-
-            for Platform {
-                solid,
-                move_sequence,
-                move_index,
-                move_timer,
-                ..
-            } in platforms.iter_mut()
-            {
-                if let Some(current) = move_sequence.get(*move_index) {
-                    *move_timer += 1;
-                    let anim_steps = match current {
-                        PlatformMove::ToTarget { target, steps } => {
-                            let position = world.solid_pos(*solid);
-                            let delta = (*target - position) / (*steps - *move_timer + 1) as f32;
-                            world.solid_move(*solid, delta);
-                            *steps
-                        }
-                        PlatformMove::Pause { steps } => *steps,
-                    };
-                    if *move_timer >= anim_steps {
-                        *move_timer = 0;
-                        *move_index = (*move_index + 1) % move_sequence.len();
-                    }
-                }
-            }
+            step_platforms(&mut world, &mut platforms, &mut groups);
+            apply_channels(&mut world, &platforms, &channels);
+            world.step_wind();
+            squash = squash.and_then(|(pad, steps)| (steps > 1).then_some((pad, steps - 1)));
+            tower.extend(&mut world, &mut platforms, pos.y);
 
             s_anim_time -= 1;
             if s_anim_time <= 0 {
@@ -720,6 +1309,11 @@ async fn main() {
             }
         }
 
+        // The ambience follows the wind blowing on the scavenger, and gets louder gliding
+        let wind = world.wind_at(Rect::new(pos.x, pos.y, 32.0, 32.0));
+        let glide = if player.gliding { 0.3 } else { 0.0 };
+        set_sound_volume(&snd_wind, (0.2 + glide + wind.length() * 4.0).min(1.0));
+
         let mut camera =
             Camera2D::from_display_rect(Rect::new(0.0, 0.0, screen_width(), screen_height()));
         camera.zoom *= vec2(1.0, -1.0);
@@ -734,27 +1328,113 @@ async fn main() {
         camera.target = camera_target;
         set_camera(&camera);
         //draw_rectangle(pos.x, pos.y, 32.0, 32.0, RED);
+        // Blinks while it cannot be hurt again
+        let hurt = player
+            .health
+            .is_some_and(|health| health.invulnerable / 4 % 2 == 1);
         draw_texture_ex(
             &scavenger,
             pos.x - 16.0,
             pos.y - 28.0,
-            WHITE,
+            if hurt { RED } else { WHITE },
             DrawTextureParams {
                 source: Some(Rect::new(s_anim_index as f32 * 34.0 + 1.0, 0.0, 32.0, 30.0)),
                 dest_size: Some(vec2(64.0, 60.0)),
-                flip_x: dx < 0.0,
+                flip_x: player.dx < 0.0,
                 ..Default::default()
             },
         );
 
-        for Platform { solid, .. } in platforms.iter() {
+        for (rect, kind) in world.climbables() {
+            let tile = match kind {
+                ClimbKind::Ladder => vec2(0.0, 48.0),
+                ClimbKind::Rope => vec2(48.0, 0.0),
+            };
+            for r in 0..(rect.h / 16.0).ceil() as i32 {
+                for c in 0..(rect.w / 16.0).ceil() as i32 {
+                    draw_texture_ex(
+                        &onebit,
+                        rect.x + c as f32 * 16.0,
+                        rect.y + r as f32 * 16.0,
+                        WHITE,
+                        DrawTextureParams {
+                            source: Some(Rect::new(tile.x, tile.y, 16.0, 16.0)),
+                            ..Default::default()
+                        },
+                    );
+                }
+            }
+        }
+
+        for volume in world.volumes() {
+            let (tile, color) = match volume.medium {
+                Medium::Water => (vec2(48.0, 80.0), Color::new(0.3, 0.5, 1.0, 0.3)),
+                Medium::Hay => (vec2(272.0, 0.0), Color::new(0.9, 0.8, 0.3, 0.3)),
+            };
+            let rect = volume.rect;
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, color);
+            for c in 0..(rect.w / 16.0) as i32 {
+                draw_texture_ex(
+                    &onebit,
+                    rect.x + c as f32 * 16.0,
+                    rect.y - 8.0,
+                    WHITE,
+                    DrawTextureParams {
+                        source: Some(Rect::new(tile.x, tile.y, 16.0, 16.0)),
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+
+        for platform in platforms.iter() {
             let Collider {
-                position: Vec2 { x, y },
+                position: Vec2 { mut x, y },
                 dimension,
                 flags,
-            } = world.solid_collider(*solid);
+                enabled,
+            } = world.solid_collider(platform.solid);
+            if !enabled {
+                continue;
+            }
+            let bouncy = world.solid_bounce(platform.solid).is_some();
+            let door = platform.door.is_some();
+            let surface = world.solid_surface(platform.solid);
+            // Deadly, crumbling, bouncy, door, conveyor and sticky platforms are made of a single
+            // repeated tile
+            let single = flags & DEADLY != 0
+                || platform.crumble.is_some()
+                || bouncy
+                || door
+                || matches!(surface, Some(Surface::Conveyor(_) | Surface::Sticky));
+            if let CrumbleState::Shaking(_) = platform.crumble_state {
+                x += gen_range(-2.0, 2.0);
+            }
+            let press = match squash {
+                Some((pad, steps)) if pad == platform.solid => {
+                    steps as f32 / SQUASH_STEPS as f32 * 8.0
+                }
+                _ => 0.0,
+            };
             let tl = if flags & DEADLY != 0 {
                 vec2(48.0, 144.0)
+            } else if bouncy {
+                vec2(80.0, 128.0)
+            } else if door {
+                vec2(256.0, 32.0)
+            } else if platform.crumble.is_some() {
+                vec2(176.0, 48.0)
+            } else if let Some(Surface::Conveyor(speed)) = surface {
+                // Arrows in the direction the belt runs
+                if speed < 0.0 {
+                    vec2(192.0, 64.0)
+                } else {
+                    vec2(208.0, 64.0)
+                }
+            } else if surface == Some(Surface::Sticky) {
+                vec2(64.0, 112.0)
+            } else if surface == Some(Surface::Ice) {
+                vec2(112.0, 64.0)
             } else if flags & NO_SLIDE != 0 {
                 vec2(240.0, 80.0)
             } else {
@@ -762,8 +1442,10 @@ async fn main() {
             };
             let rows = (dimension.y / 16.0) as i32;
             let cols = (dimension.x / 16.0) as i32;
+            // Pressed in pads are drawn squashed from the top
+            let tile_height = 16.0 - press / rows as f32;
             for r in 0..rows {
-                let dy = if r == 0 || flags & DEADLY != 0 {
+                let dy = if r == 0 || single {
                     0.0
                 } else if r == rows - 1 {
                     32.0
@@ -771,7 +1453,7 @@ async fn main() {
                     16.0
                 };
                 for c in 0..cols {
-                    let dx = if c == 0 || flags & DEADLY != 0 {
+                    let dx = if c == 0 || single {
                         0.0
                     } else if c == cols - 1 {
                         32.0
@@ -784,10 +1466,11 @@ async fn main() {
                     draw_texture_ex(
                         &onebit,
                         x + c as f32 * 16.0,
-                        y + r as f32 * 16.0,
+                        y + press + r as f32 * tile_height,
                         WHITE,
                         DrawTextureParams {
                             source: Some(Rect::new(tl.x + dx, tl.y + dy, 16.0, 16.0)),
+                            dest_size: Some(vec2(16.0, tile_height)),
                             ..Default::default()
                         },
                     );
@@ -795,6 +1478,104 @@ async fn main() {
             }
         }
 
+        for pickup in pickups.iter() {
+            let usable = pickup.kind != PickupKind::Life || mode == RunMode::Lives;
+            if !usable || !world.actor_has_flag(pickup.actor, NOT_TAKEN) {
+                continue;
+            }
+            let tile = match pickup.kind {
+                PickupKind::Ability(_) => vec2(32.0, 16.0),
+                PickupKind::Heal(_) => vec2(32.0, 32.0),
+                PickupKind::Life => vec2(16.0, 32.0),
+            };
+            let position = world.actor_pos(pickup.actor);
+            draw_texture_ex(
+                &onebit,
+                position.x,
+                position.y,
+                WHITE,
+                DrawTextureParams {
+                    source: Some(Rect::new(tile.x, tile.y, 16.0, 16.0)),
+                    dest_size: Some(vec2(32.0, 32.0)),
+                    ..Default::default()
+                },
+            );
+        }
+
+        for rock in rockfalls.iter().flat_map(|rockfall| &rockfall.rocks) {
+            let position = world.actor_pos(rock.actor);
+            draw_texture_ex(
+                &onebit,
+                position.x,
+                position.y,
+                WHITE,
+                DrawTextureParams {
+                    source: Some(Rect::new(176.0, 48.0, 16.0, 16.0)),
+                    ..Default::default()
+                },
+            );
+        }
+
+        for enemy in enemies.iter() {
+            let tile = match enemy.behavior {
+                Behavior::Patrol { .. } => vec2(0.0, 208.0),
+                Behavior::Bat { .. } => vec2(96.0, 192.0),
+                Behavior::Turret { .. } => vec2(0.0, 224.0),
+            };
+            let position = world.actor_pos(enemy.actor);
+            draw_texture_ex(
+                &onebit,
+                position.x,
+                position.y,
+                WHITE,
+                DrawTextureParams {
+                    source: Some(Rect::new(tile.x, tile.y, 16.0, 16.0)),
+                    dest_size: Some(vec2(32.0, 32.0)),
+                    flip_x: enemy.velocity.x < 0.0,
+                    ..Default::default()
+                },
+            );
+            for shot in enemy.shots.iter() {
+                let position = world.actor_pos(shot.actor);
+                draw_texture_ex(
+                    &onebit,
+                    position.x,
+                    position.y,
+                    WHITE,
+                    DrawTextureParams {
+                        source: Some(Rect::new(16.0, 16.0, 16.0, 16.0)),
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+
+        for (index, switch) in switches.iter().enumerate() {
+            let pressed = inside_switches.get(index).copied().unwrap_or_default();
+            let position = world.actor_pos(switch.actor);
+            let (tile, press) = if world.actor_has_flag(switch.actor, KEY | NOT_TAKEN) {
+                (vec2(256.0, 64.0), 0.0)
+            } else if world.actor_has_flag(switch.actor, LEVER) {
+                let on = channels[switch.channel].on;
+                (vec2(if on { 272.0 } else { 256.0 }, 48.0), 0.0)
+            } else if world.actor_has_flag(switch.actor, PLATE) {
+                (vec2(176.0, 64.0), if pressed { 28.0 } else { 24.0 })
+            } else {
+                continue;
+            };
+            draw_texture_ex(
+                &onebit,
+                position.x,
+                position.y + press,
+                WHITE,
+                DrawTextureParams {
+                    source: Some(Rect::new(tile.x, tile.y, 16.0, 16.0)),
+                    dest_size: Some(vec2(32.0, 32.0 - press)),
+                    ..Default::default()
+                },
+            );
+        }
+
         for (_, coin_collider) in world.actors() {
             if coin_collider.flags & (COIN | NOT_TAKEN) != COIN | NOT_TAKEN {
                 continue;
@@ -813,16 +1594,19 @@ async fn main() {
         }
 
         // Drop death indicator
-        if gen_range(0.0, 1.0) > 0.7 {
-            let mut tmp_p_y = pos.y + 32.0;
-            let mut tmp_v_y = dy;
-            loop {
-                tmp_p_y += tmp_v_y;
-                tmp_v_y += GRAVITY.y;
-                if tmp_v_y > 8.0 {
-                    break;
-                }
+        let mut tmp_p_y = pos.y + 32.0;
+        let mut tmp_v_y = player.dy;
+        for _ in 0..DROP_LINE_STEPS {
+            tmp_p_y += tmp_v_y;
+            tmp_v_y += GRAVITY.y;
+            if tmp_v_y > player.config.fatal_fall {
+                break;
             }
+        }
+        if assists.safe_drop_line {
+            draw_line(pos.x - 128.0, tmp_p_y, pos.x + 160.0, tmp_p_y, 2.0, RED);
+        }
+        if gen_range(0.0, 1.0) > 0.7 {
             world.add_particle(
                 vec2(gen_range(pos.x - 128.0, pos.x + 128.0), tmp_p_y),
                 vec2(gen_range(-1.0, 1.0), -1.0),
@@ -831,8 +1615,8 @@ async fn main() {
 
         draw_texture_ex(
             &onebit,
-            -32.0,
-            0.0,
+            level.start.x - 32.0,
+            level.start.y,
             WHITE,
             DrawTextureParams {
                 source: Some(Rect::new(32.0, 80.0, 16.0, 16.0)),
@@ -840,59 +1624,34 @@ async fn main() {
             },
         );
 
-        // In-game tutorial, mostly sorted top to bottom
-        draw_text(
-            "How did I end up on this tower? I need to get down.",
-            -470.0,
-            -120.0,
-            40.0,
-            WHITE,
-        );
-
-        draw_text("My trusty old soul-stone.", -80.0, -52.0, 24.0, WHITE);
-        draw_text(
-            "Should anything happen to me, I will be returned here.",
-            -150.0,
-            -26.0,
-            24.0,
-            WHITE,
-        );
-
-        draw_text(
-            "I can roughly sense, how far I can jump down.",
-            -250.0,
-            330.0,
-            24.0,
-            WHITE,
-        );
-
-        draw_text("This, I can barely reach.", 250.0, 330.0, 24.0, WHITE);
-
-        draw_text("I can slide down here.", 150.0, 410.0, 24.0, WHITE);
-
-        draw_text(
-            "The platform is too far to jump down.",
-            150.0,
-            430.0,
-            24.0,
-            WHITE,
-        );
+        for (index, stone) in level.checkpoints.iter().enumerate() {
+            let active = checkpoint
+                .as_ref()
+                .is_some_and(|active| active.index == index);
+            draw_texture_ex(
+                &onebit,
+                stone.x - 32.0,
+                stone.y,
+                if active { WHITE } else { GRAY },
+                DrawTextureParams {
+                    source: Some(Rect::new(32.0, 80.0, 16.0, 16.0)),
+                    ..Default::default()
+                },
+            );
+        }
 
-        draw_text(
-            "When sliding, I can jump from the wall.",
-            -64.0,
-            900.0,
-            24.0,
-            WHITE,
-        );
+        for LevelText {
+            position,
+            size,
+            text,
+        } in level.texts.iter()
+        {
+            draw_text(text, position.x, position.y, *size, WHITE);
+        }
 
-        draw_text(
-            "Some walls, I cannot slide down.",
-            200.0,
-            1150.0,
-            24.0,
-            WHITE,
-        );
+        for (index, wind) in world.winds().iter().enumerate() {
+            draw_wind_streaks(wind, index, world.wind_clock());
+        }
 
         for particle in world.particles() {
             draw_line(
@@ -906,25 +1665,95 @@ async fn main() {
         }
 
         set_default_camera();
+        draw_text(&height_text(&tower, &level, pos), 0.0, 28.0, 30.0, WHITE);
+        draw_text(&format!("Diamonds: {}", coins), 0.0, 60.0, 30.0, WHITE);
+        let segments: Vec<String> = (0..=level.checkpoints.len())
+            .map(|segment| deaths.get(segment).copied().unwrap_or(0).to_string())
+            .collect();
         draw_text(
-            &format!("Height: {:.1}m", (*level - pos.y - 32.0) / 16.0),
-            0.0,
-            28.0,
+            &format!("Deaths: {}", segments.join(" | ")),
+            240.0,
+            60.0,
             30.0,
             WHITE,
         );
-        draw_text(&format!("Diamonds: {}", coins), 0.0, 60.0, 30.0, WHITE);
-        let s = timer / 60;
-        let ms = ((timer % 60) as f32 / 60.0 * 1000.0) as i32;
-        let m = s / 60;
-        let s = s % 60;
         draw_text(
-            &format!("Time: {:02}:{:02}:{:03}", m, s, ms),
+            &format!("Time: {}", daily::format_time(timer)),
             0.0,
             100.0,
             40.0,
             WHITE,
         );
+        if mode == RunMode::Lives {
+            draw_text(&format!("Lives: {}", lives), 520.0, 100.0, 30.0, WHITE);
+        }
+        if let Some(health) = player.health {
+            let fill = health.hp as f32 / health.max as f32;
+            draw_rectangle(300.0, 76.0, 200.0 * fill, 20.0, WHITE);
+            draw_rectangle_lines(300.0, 76.0, 200.0, 20.0, 2.0, WHITE);
+        }
+        let title = match tower {
+            Tower::Level(_) => match level.par {
+                Some(par) => format!("{}  par {}", level.name, daily::format_time(par)),
+                None => level.name.clone(),
+            },
+            Tower::Daily { scored: true, .. } => format!("{} (scored attempt)", level.name),
+            _ => level.name.clone(),
+        };
+        let title = if practice || matches!(tower, Tower::Daily { scored: false, .. }) {
+            format!("{} (practice)", title)
+        } else {
+            title
+        };
+        let title = if assisted {
+            format!("{} (assisted)", title)
+        } else {
+            title
+        };
+        let title = match mode {
+            RunMode::Normal => title,
+            mode => format!("{} ({})", title, mode.label().to_lowercase()),
+        };
+        draw_text(&title, 0.0, 130.0, 24.0, WHITE);
+        draw_text(&message, 0.0, 160.0, 24.0, WHITE);
+        if let Some(results) = &run_over {
+            let x = screen_width() / 2.0 - 300.0;
+            draw_text(results, x, screen_height() / 2.0, 30.0, WHITE);
+            let help = "Press Enter to start over";
+            draw_text(help, x, screen_height() / 2.0 + 36.0, 24.0, WHITE);
+        }
+        if let Some(selected) = assist_menu {
+            let x = screen_width() / 2.0 - 200.0;
+            draw_text("Assists (paused)", x, 200.0, 30.0, WHITE);
+            let help = "Up/Down select, Left/Right change, Tab to play";
+            draw_text(help, x, 226.0, 20.0, WHITE);
+            for (index, line) in assist_lines(&assists).iter().enumerate() {
+                let marker = if index == selected { ">" } else { " " };
+                let y = 256.0 + index as f32 * 26.0;
+                draw_text(&format!("{} {}", marker, line), x, y, 24.0, WHITE);
+            }
+        }
+        if let Some(selected) = tuning_panel {
+            let help = "Tuning: PageUp/PageDown select, [ and ] change, \\ save, ` to close";
+            draw_text(help, 0.0, 200.0, 24.0, WHITE);
+            for (index, (name, _, _)) in TUNING.iter().enumerate() {
+                let marker = if index == selected { ">" } else { " " };
+                let value = player.config.get(name).unwrap_or_default();
+                let line = format!("{} {} {:.3}", marker, name, value);
+                draw_text(&line, 0.0, 226.0 + index as f32 * 22.0, 22.0, WHITE);
+            }
+        }
+        if show_history {
+            draw_text("Daily results", screen_width() - 400.0, 28.0, 30.0, WHITE);
+            for (index, attempt) in history.attempts.iter().rev().take(20).enumerate() {
+                let line = match attempt.result {
+                    Some(result) => result.to_string(),
+                    None => format!("{}  failed", daily::date(attempt.day)),
+                };
+                let y = 60.0 + index as f32 * 26.0;
+                draw_text(&line, screen_width() - 400.0, y, 24.0, WHITE);
+            }
+        }
 
         next_frame().await
     }
@@ -0,0 +1,201 @@
+use macroquad::prelude::*;
+use std::f32::consts::PI;
+
+/// How a platform speeds up and slows down on its way to a target.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    /// Bounces off the target a few times before settling.
+    Bounce,
+    /// Overshoots the target and swings back.
+    Elastic,
+}
+
+const EASING_NAMES: [(&str, Easing); 12] = [
+    ("linear", Easing::Linear),
+    ("quad_in", Easing::QuadIn),
+    ("quad_out", Easing::QuadOut),
+    ("quad_in_out", Easing::QuadInOut),
+    ("cubic_in", Easing::CubicIn),
+    ("cubic_out", Easing::CubicOut),
+    ("cubic_in_out", Easing::CubicInOut),
+    ("sine_in", Easing::SineIn),
+    ("sine_out", Easing::SineOut),
+    ("sine_in_out", Easing::SineInOut),
+    ("bounce", Easing::Bounce),
+    ("elastic", Easing::Elastic),
+];
+
+impl Easing {
+    /// The easing called `name` in level files.
+    pub fn from_name(name: &str) -> Option<Easing> {
+        EASING_NAMES
+            .iter()
+            .find(|(easing_name, _)| *easing_name == name)
+            .map(|(_, easing)| *easing)
+    }
+
+    /// Share of the way to the target after `t` of the move, 0 at the start and 1 at the end.
+    pub fn apply(&self, t: f32) -> f32 {
+        if t <= 0.0 {
+            return 0.0;
+        }
+        if t >= 1.0 {
+            return 1.0;
+        }
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::SineIn => 1.0 - (t * PI / 2.0).cos(),
+            Easing::SineOut => (t * PI / 2.0).sin(),
+            Easing::SineInOut => -((t * PI).cos() - 1.0) / 2.0,
+            Easing::Bounce => {
+                // Parabolas of shrinking height, see https://easings.net/#easeOutBounce
+                let (n, d) = (7.5625, 2.75);
+                if t < 1.0 / d {
+                    n * t * t
+                } else if t < 2.0 / d {
+                    let t = t - 1.5 / d;
+                    n * t * t + 0.75
+                } else if t < 2.5 / d {
+                    let t = t - 2.25 / d;
+                    n * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / d;
+                    n * t * t + 0.984375
+                }
+            }
+            Easing::Elastic => {
+                2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+            }
+        }
+    }
+}
+
+/// What a platform does once it reached the end of its move sequence.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Playback {
+    /// Starts over with the first move.
+    #[default]
+    Loop,
+    /// Plays the moves backwards to where it started, then forwards again.
+    PingPong,
+    /// Stays at the end.
+    Once,
+}
+
+impl Playback {
+    pub fn from_name(name: &str) -> Option<Playback> {
+        match name {
+            "loop" => Some(Playback::Loop),
+            "ping_pong" => Some(Playback::PingPong),
+            "once" => Some(Playback::Once),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SplineKind {
+    /// Passes through every point.
+    CatmullRom,
+    /// Starts and ends at the first and last point, the ones in between only pull on it.
+    Bezier,
+}
+
+impl SplineKind {
+    pub fn from_name(name: &str) -> Option<SplineKind> {
+        match name {
+            "catmull_rom" => Some(SplineKind::CatmullRom),
+            "bezier" => Some(SplineKind::Bezier),
+            _ => None,
+        }
+    }
+
+    /// Point on the curve through `points` after `t` of the way, 0 at the first point.
+    pub fn at(&self, points: &[Vec2], t: f32) -> Vec2 {
+        match self {
+            SplineKind::CatmullRom => catmull_rom_at(points, t),
+            SplineKind::Bezier => bezier_at(points, t),
+        }
+    }
+}
+
+/// Point on the polyline through `points` after `t` of its length, so it is passed at
+/// constant speed.
+pub fn polyline_at(points: &[Vec2], t: f32) -> Vec2 {
+    let length: f32 = points
+        .windows(2)
+        .map(|pair| pair[0].distance(pair[1]))
+        .sum();
+    let mut distance = length * t.clamp(0.0, 1.0);
+    for pair in points.windows(2) {
+        let segment = pair[0].distance(pair[1]);
+        if distance <= segment && segment > 0.0 {
+            return pair[0].lerp(pair[1], distance / segment);
+        }
+        distance -= segment;
+    }
+    points.last().copied().unwrap_or_default()
+}
+
+fn catmull_rom_at(points: &[Vec2], t: f32) -> Vec2 {
+    if points.len() < 2 {
+        return points.first().copied().unwrap_or_default();
+    }
+    let segments = points.len() - 1;
+    let position = t.clamp(0.0, 1.0) * segments as f32;
+    let index = (position as usize).min(segments - 1);
+    let t = position - index as f32;
+    // The curve ends at the first and last point, as if they were there twice
+    let p0 = points[index.saturating_sub(1)];
+    let p1 = points[index];
+    let p2 = points[index + 1];
+    let p3 = points[(index + 2).min(segments)];
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+fn bezier_at(points: &[Vec2], t: f32) -> Vec2 {
+    // De Casteljau
+    let t = t.clamp(0.0, 1.0);
+    let mut points = points.to_vec();
+    while points.len() > 1 {
+        points = points
+            .windows(2)
+            .map(|pair| pair[0].lerp(pair[1], t))
+            .collect();
+    }
+    points.first().copied().unwrap_or_default()
+}
@@ -1,5 +1,6 @@
 use macroquad::prelude::*;
-use slotmap::{new_key_type, DefaultKey, SlotMap};
+use slotmap::{new_key_type, DefaultKey, SecondaryMap, SlotMap};
+use std::f32::consts::PI;
 
 pub const GRAVITY: Vec2 = vec2(0.0, 6.0 / 60.0);
 
@@ -7,7 +8,9 @@ pub const GRAVITY: Vec2 = vec2(0.0, 6.0 / 60.0);
 pub struct Collider {
     pub position: Vec2,
     pub dimension: Vec2,
-    pub flags: u8,
+    pub flags: u16,
+    /// Nothing collides with disabled solids, they keep their key to be enabled again later.
+    pub enabled: bool,
 }
 
 impl Collider {
@@ -28,6 +31,130 @@ pub struct Particle {
     pub life_time_steps: u32,
 }
 
+/// What the surface of a solid does to actors standing on or sliding down it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Surface {
+    /// Carries standing actors along with the given pixels per step.
+    Conveyor(f32),
+    /// Hard to speed up and slow down on.
+    Ice,
+    /// Slows down actors sliding down it.
+    Sticky,
+}
+
+/// How the force of a wind zone changes over time.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Gust {
+    Constant,
+    /// Rises and falls smoothly every given number of steps.
+    Cycle(u32),
+    /// Changes to a random strength about every given number of steps.
+    Noise(u32),
+}
+
+/// Area pushing actors and particles inside it.
+#[derive(Copy, Clone, Debug)]
+pub struct Wind {
+    pub rect: Rect,
+    /// Added to the velocity every step when blowing at full strength.
+    pub force: Vec2,
+    pub gust: Gust,
+}
+
+impl Wind {
+    /// Share of the full force blowing at step `t`, between 0 and 1.
+    pub fn strength(&self, t: u32) -> f32 {
+        match self.gust {
+            Gust::Constant => 1.0,
+            Gust::Cycle(period) => 0.5 - 0.5 * (t as f32 / period as f32 * 2.0 * PI).cos(),
+            Gust::Noise(period) => {
+                // Smoothed steps between random values, the same for every run
+                let index = t / period;
+                let t = (t % period) as f32 / period as f32;
+                let t = t * t * (3.0 - 2.0 * t);
+                noise(index) + (noise(index + 1) - noise(index)) * t
+            }
+        }
+    }
+
+    pub fn force_at(&self, t: u32) -> Vec2 {
+        self.force * self.strength(t)
+    }
+}
+
+/// What a soft volume is filled with.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Medium {
+    Water,
+    Hay,
+}
+
+impl Medium {
+    /// Fastest fall inside, faster actors are slowed down to it.
+    pub fn max_fall(&self) -> f32 {
+        match self {
+            Medium::Water => 2.0,
+            Medium::Hay => 1.0,
+        }
+    }
+
+    /// Share of the fall speed above `max_fall` lost every step.
+    pub fn damping(&self) -> f32 {
+        match self {
+            Medium::Water => 0.2,
+            Medium::Hay => 0.4,
+        }
+    }
+
+    /// Share of the usual control left inside.
+    pub fn control(&self) -> f32 {
+        match self {
+            Medium::Water => 0.5,
+            Medium::Hay => 0.25,
+        }
+    }
+}
+
+/// Area actors fall into safely, like water or a pile of hay.
+#[derive(Copy, Clone, Debug)]
+pub struct Volume {
+    pub rect: Rect,
+    pub medium: Medium,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ClimbKind {
+    Ladder,
+    Rope,
+}
+
+/// Area actors can hold on to and climb.
+#[derive(Copy, Clone, Debug)]
+pub struct Climbable {
+    /// Relative to the position of `anchor` if there is one.
+    pub rect: Rect,
+    pub kind: ClimbKind,
+    /// Solid the climbable hangs from and moves with.
+    pub anchor: Option<Solid>,
+}
+
+/// Random value between 0 and 1 for `n`.
+fn noise(n: u32) -> f32 {
+    let mut x = n.wrapping_mul(0x9E37_79B9);
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x85EB_CA6B);
+    x ^= x >> 13;
+    x as f32 / u32::MAX as f32
+}
+
+fn wind_force(winds: &[Wind], t: u32, rect: Rect) -> Vec2 {
+    winds
+        .iter()
+        .filter(|wind| wind.rect.overlaps(&rect))
+        .map(|wind| wind.force_at(t))
+        .sum()
+}
+
 new_key_type! {
     pub struct Actor;
     pub struct Solid;
@@ -37,7 +164,15 @@ new_key_type! {
 pub struct World {
     actors: SlotMap<Actor, Collider>,
     solids: SlotMap<Solid, Collider>,
+    /// Vertical speed given to actors landing on a solid, instead of stopping them.
+    bounces: SecondaryMap<Solid, f32>,
+    surfaces: SecondaryMap<Solid, Surface>,
     particles: SlotMap<DefaultKey, Particle>,
+    winds: Vec<Wind>,
+    volumes: Vec<Volume>,
+    climbables: Vec<Climbable>,
+    /// Steps the winds have been blowing for.
+    wind_clock: u32,
 }
 
 impl World {
@@ -64,7 +199,8 @@ impl World {
 
     pub fn step_particles(&mut self) {
         for particle in self.particles.values_mut() {
-            particle.velocity += GRAVITY;
+            let point = Rect::new(particle.position.x, particle.position.y, 0.0, 0.0);
+            particle.velocity += GRAVITY + wind_force(&self.winds, self.wind_clock, point);
             particle.last_position = particle.position;
             particle.position += particle.velocity;
             particle.life_time_steps -= 1;
@@ -73,14 +209,86 @@ impl World {
             .retain(|_, particle| particle.life_time_steps > 0);
     }
 
-    pub fn add_actor(&mut self, position: Vec2, dimension: Vec2, flags: u8) -> Actor {
+    pub fn add_wind(&mut self, wind: Wind) {
+        self.winds.push(wind);
+    }
+
+    pub fn winds(&self) -> &[Wind] {
+        &self.winds
+    }
+
+    pub fn wind_clock(&self) -> u32 {
+        self.wind_clock
+    }
+
+    pub fn set_wind_clock(&mut self, t: u32) {
+        self.wind_clock = t;
+    }
+
+    pub fn step_wind(&mut self) {
+        self.wind_clock += 1;
+    }
+
+    /// Force of all winds blowing on `rect` right now.
+    pub fn wind_at(&self, rect: Rect) -> Vec2 {
+        wind_force(&self.winds, self.wind_clock, rect)
+    }
+
+    pub fn add_volume(&mut self, volume: Volume) {
+        self.volumes.push(volume);
+    }
+
+    pub fn volumes(&self) -> &[Volume] {
+        &self.volumes
+    }
+
+    /// What `rect` is in, the first volume it overlaps.
+    pub fn medium_at(&self, rect: Rect) -> Option<Medium> {
+        self.volumes
+            .iter()
+            .find(|volume| volume.rect.overlaps(&rect))
+            .map(|volume| volume.medium)
+    }
+
+    pub fn add_climbable(&mut self, climbable: Climbable) {
+        self.climbables.push(climbable);
+    }
+
+    /// Where the climbables are right now, with their kind.
+    pub fn climbables(&self) -> impl Iterator<Item = (Rect, ClimbKind)> + '_ {
+        self.climbables.iter().map(|climbable| {
+            let mut rect = climbable.rect;
+            if let Some(anchor) = climbable.anchor {
+                rect = rect.offset(self.solids[anchor].position);
+            }
+            (rect, climbable.kind)
+        })
+    }
+
+    /// The first climbable overlapping `rect`.
+    pub fn climbable_at(&self, rect: Rect) -> Option<Rect> {
+        self.climbables()
+            .map(|(climbable, _)| climbable)
+            .find(|climbable| climbable.overlaps(&rect))
+    }
+
+    pub fn add_actor(&mut self, position: Vec2, dimension: Vec2, flags: u16) -> Actor {
         self.actors.insert(Collider {
             position,
             dimension,
             flags,
+            enabled: true,
         })
     }
 
+    pub fn remove_actor(&mut self, actor: Actor) {
+        self.actors.remove(actor);
+    }
+
+    pub fn actor_collider(&self, actor: Actor) -> Collider {
+        self.actors[actor]
+    }
+
     pub fn set_actor_pos(&mut self, actor: Actor, position: Vec2) {
         self.actors[actor].position = position;
     }
@@ -89,11 +297,12 @@ impl World {
         self.actors[actor].position
     }
 
-    pub fn add_solid(&mut self, position: Vec2, dimension: Vec2, flags: u8) -> Solid {
+    pub fn add_solid(&mut self, position: Vec2, dimension: Vec2, flags: u16) -> Solid {
         self.solids.insert(Collider {
             position,
             dimension,
             flags,
+            enabled: true,
         })
     }
 
@@ -101,23 +310,48 @@ impl World {
         self.solids[solid].position = position;
     }
 
+    pub fn set_solid_enabled(&mut self, solid: Solid, enabled: bool) {
+        self.solids[solid].enabled = enabled;
+    }
+
+    pub fn solid_enabled(&self, solid: Solid) -> bool {
+        self.solids[solid].enabled
+    }
+
+    /// Makes actors landing on `solid` leave it with the vertical speed `launch`.
+    pub fn set_solid_bounce(&mut self, solid: Solid, launch: f32) {
+        self.bounces.insert(solid, launch);
+    }
+
+    pub fn solid_bounce(&self, solid: Solid) -> Option<f32> {
+        self.bounces.get(solid).copied()
+    }
+
+    pub fn set_solid_surface(&mut self, solid: Solid, surface: Surface) {
+        self.surfaces.insert(solid, surface);
+    }
+
+    pub fn solid_surface(&self, solid: Solid) -> Option<Surface> {
+        self.surfaces.get(solid).copied()
+    }
+
     pub fn solid_collider(&self, solid: Solid) -> Collider {
         self.solids[solid]
     }
 
-    pub fn actor_set_flag(&mut self, actor: Actor, flag: u8) {
+    pub fn actor_set_flag(&mut self, actor: Actor, flag: u16) {
         self.actors[actor].flags |= flag;
     }
 
-    pub fn actor_unset_flag(&mut self, actor: Actor, flag: u8) {
+    pub fn actor_unset_flag(&mut self, actor: Actor, flag: u16) {
         self.actors[actor].flags &= !flag;
     }
 
-    pub fn actor_has_flag(&self, actor: Actor, flag: u8) -> bool {
+    pub fn actor_has_flag(&self, actor: Actor, flag: u16) -> bool {
         self.actors[actor].flags & flag == flag
     }
 
-    pub fn solid_has_flag(&self, solid: Solid, flag: u8) -> bool {
+    pub fn solid_has_flag(&self, solid: Solid, flag: u16) -> bool {
         self.solids[solid].flags & flag == flag
     }
 
@@ -130,7 +364,7 @@ impl World {
         let my_rect = collider.as_rect();
         for actor_collider in self.actors.values_mut() {
             let actor_rect = actor_collider.as_rect();
-            if actor_rect.overlaps(&my_rect) {
+            if collider.enabled && actor_rect.overlaps(&my_rect) {
                 actor_collider.position += delta;
             }
         }
@@ -159,13 +393,13 @@ impl World {
             }
         }
         let collider = &mut self.actors[actor];
-        for (solid, solid_collider) in self.solids.iter_mut() {
+        for (solid, solid_collider) in self.solids.iter_mut().filter(|(_, solid)| solid.enabled) {
             let solid_rect = solid_collider.as_rect();
             if let Some(intersection) = solid_rect.intersect(actor_rect) {
                 if dy > 0.0 {
                     collider.position.y = intersection.y - collider.dimension.y;
                 } else {
-                    collider.position.y = intersection.top();
+                    collider.position.y = intersection.bottom();
                 }
                 return (Some(solid), hit_actor);
             }
@@ -196,7 +430,7 @@ impl World {
             }
         }
         let collider = &mut self.actors[actor];
-        for (solid, solid_collider) in self.solids.iter_mut() {
+        for (solid, solid_collider) in self.solids.iter_mut().filter(|(_, solid)| solid.enabled) {
             let solid_rect = solid_collider.as_rect();
             if let Some(intersection) = solid_rect.intersect(actor_rect) {
                 if dx > 0.0 && solid_rect.left() > actor_rect.left() {
@@ -215,6 +449,7 @@ impl World {
         let rect = Rect::new(position.x, position.y, dimension.x, dimension.y);
         self.solids
             .iter()
+            .filter(|(_, solid_collider)| solid_collider.enabled)
             .filter_map(|(solid, solid_collider)| {
                 (solid_collider.as_rect().intersect(rect)).map(|rect| (solid, rect))
             })
@@ -0,0 +1,463 @@
+use crate::controller::*;
+use crate::level::*;
+use crate::physics::*;
+use macroquad::prelude::*;
+
+/// Landing faster than this is fatal with the classic tuning.
+pub const FATAL_FALL: f32 = 8.0;
+
+/// Share of the usual ground control left on ice.
+const ICE_CONTROL: f32 = 0.25;
+/// Share of the usual ground friction left on ice.
+const ICE_FRICTION: f32 = 0.1;
+/// Fastest slide down a sticky wall.
+const STICKY_SLIDE_SPEED: f32 = 0.5;
+/// Pixels per step climbing up or down ladders and ropes.
+const CLIMB_SPEED: f32 = 2.0;
+/// Upwards speed of the mid-air jump.
+const AIR_JUMP_SPEED: f32 = 4.0;
+const DASH_SPEED: f32 = 10.0;
+const DASH_STEPS: i32 = 10;
+/// Fastest fall while gliding, well below `FATAL_FALL`.
+const GLIDE_FALL: f32 = 1.5;
+/// Hit points of the health mode when the level does not say.
+pub const DEFAULT_HEALTH: i32 = 100;
+/// Damage of deadly solids, enemies and rocks in the health mode.
+pub const HAZARD_DAMAGE: i32 = 34;
+/// Damage per pixel per step a landing is faster than the fatal fall of the classic mode.
+const FALL_DAMAGE: f32 = 25.0;
+/// Steps after getting hurt the scavenger cannot be hurt again.
+const HURT_STEPS: i32 = 60;
+/// Upwards speed when a deadly solid hurts without killing.
+const HAZARD_LAUNCH: f32 = 4.0;
+
+/// Starts a grace period of `steps` over on `reset`, or counts it down.
+fn count_down(left: i32, reset: bool, steps: i32) -> i32 {
+    if reset {
+        steps
+    } else {
+        (left - 1).max(0)
+    }
+}
+
+/// Highest drop the scavenger survives without sliding, measured from the ledge it walks off,
+/// with the classic tuning.
+pub fn max_safe_drop() -> f32 {
+    let mut height = 0.0;
+    let mut dy = GRAVITY.y;
+    while dy <= FATAL_FALL {
+        height += dy;
+        dy += GRAVITY.y;
+    }
+    height
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Input {
+    pub left: bool,
+    pub right: bool,
+    pub up: bool,
+    pub down: bool,
+    /// Jumps in mid-air, only for the first step the button is down.
+    pub jump: bool,
+    /// Dashes in mid-air, only for the first step the button is down.
+    pub dash: bool,
+    pub glide: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Ability {
+    Dash,
+    DoubleJump,
+    Glide,
+}
+
+impl Ability {
+    pub fn from_name(name: &str) -> Option<Ability> {
+        match name {
+            "dash" => Some(Ability::Dash),
+            "double_jump" => Some(Ability::DoubleJump),
+            "glide" => Some(Ability::Glide),
+            _ => None,
+        }
+    }
+}
+
+/// What the scavenger has learned besides running and wall jumping.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Abilities {
+    pub dash: bool,
+    pub double_jump: bool,
+    pub glide: bool,
+}
+
+impl Abilities {
+    pub fn grant(&mut self, ability: Ability) {
+        match ability {
+            Ability::Dash => self.dash = true,
+            Ability::DoubleJump => self.double_jump = true,
+            Ability::Glide => self.glide = true,
+        }
+    }
+
+    pub fn has(&self, ability: Ability) -> bool {
+        match ability {
+            Ability::Dash => self.dash,
+            Ability::DoubleJump => self.double_jump,
+            Ability::Glide => self.glide,
+        }
+    }
+}
+
+/// Abilities left to use before landing again.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Charges {
+    pub air_jump: bool,
+    pub dash: bool,
+    /// Steps left of the current dash.
+    pub dash_timer: i32,
+}
+
+/// Hit points of the health mode, where falls and hazards hurt instead of killing at once.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Health {
+    pub hp: i32,
+    pub max: i32,
+    /// Steps left the scavenger cannot be hurt again.
+    pub invulnerable: i32,
+}
+
+impl Health {
+    pub fn new(max: i32) -> Health {
+        Health {
+            hp: max,
+            max,
+            invulnerable: 0,
+        }
+    }
+
+    pub fn heal(&mut self, amount: i32) {
+        self.hp = (self.hp + amount).min(self.max);
+    }
+}
+
+/// Options making the game easier, runs with any of them on are tagged as assisted.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Assists {
+    /// Share of the usual game speed, from 0.7 to 1.
+    pub speed: f32,
+    /// Always shows how far the scavenger can fall without dying.
+    pub safe_drop_line: bool,
+    /// Deadly solids can be touched safely.
+    pub deadly_immunity: bool,
+    /// Every landing is survived.
+    pub fall_immunity: bool,
+    /// Walls marked `NO_SLIDE` can be slid down and jumped off too.
+    pub slide_everywhere: bool,
+}
+
+impl Default for Assists {
+    fn default() -> Self {
+        Assists {
+            speed: 1.0,
+            safe_drop_line: false,
+            deadly_immunity: false,
+            fall_immunity: false,
+            slide_everywhere: false,
+        }
+    }
+}
+
+impl Assists {
+    pub fn any(&self) -> bool {
+        *self != Assists::default()
+    }
+}
+
+/// Coyote time and buffered presses, counted down every step.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Grace {
+    /// Steps left to wall jump off the wall last slid along.
+    pub wall: i32,
+    /// Whether that wall was to the left, so jumping off it goes right.
+    pub wall_left: bool,
+    /// Steps left to air jump without using up the air jump after walking off a ledge.
+    pub ledge: i32,
+    /// Steps left and right presses are remembered.
+    pub left: i32,
+    pub right: i32,
+    /// Directions held last step, to tell new presses apart.
+    pub held_left: bool,
+    pub held_right: bool,
+}
+
+pub struct Scavenger {
+    pub actor: Actor,
+    pub dx: f32,
+    pub dy: f32,
+    /// Holding on to a ladder or rope, which cancels gravity.
+    pub climbing: bool,
+    pub abilities: Abilities,
+    pub charges: Charges,
+    pub gliding: bool,
+    pub config: ControllerConfig,
+    pub grace: Grace,
+    /// Hit points in the health mode, any damage is fatal without.
+    pub health: Option<Health>,
+    pub assists: Assists,
+}
+
+/// What happened during a single fixed step of the scavenger.
+pub struct Step {
+    pub floor: Option<Solid>,
+    pub wall: Option<(Solid, Rect)>,
+    /// Actors touched while moving horizontally and vertically.
+    pub touched: [Option<Actor>; 2],
+    /// Vertical speed right before landing on `floor`.
+    pub impact: f32,
+    pub died: bool,
+    /// Lost hit points without dying.
+    pub hurt: bool,
+    /// Landed on a bouncy floor and was launched up again.
+    pub bounced: bool,
+    /// What the scavenger is in after moving.
+    pub medium: Option<Medium>,
+    pub jumped: bool,
+    pub dashed: bool,
+}
+
+impl Scavenger {
+    pub fn new(world: &mut World, position: Vec2) -> Self {
+        Scavenger {
+            actor: world.add_actor(position, vec2(32.0, 32.0), BODY),
+            dx: 0.0,
+            dy: 1.0,
+            climbing: false,
+            abilities: Abilities::default(),
+            charges: Charges::default(),
+            gliding: false,
+            config: ControllerConfig::default(),
+            grace: Grace::default(),
+            health: None,
+            assists: Assists::default(),
+        }
+    }
+
+    /// Takes `damage`, true if that kills the scavenger. Does nothing while invulnerable.
+    pub fn hurt(&mut self, damage: i32) -> bool {
+        match &mut self.health {
+            None => damage > 0,
+            Some(health) if damage <= 0 || health.invulnerable > 0 => false,
+            Some(health) => {
+                health.hp = (health.hp - damage).max(0);
+                health.invulnerable = HURT_STEPS;
+                health.hp == 0
+            }
+        }
+    }
+
+    /// Moves the scavenger and applies one step of controls and gravity.
+    /// Dying only stops the scavenger, putting it back is up to the caller.
+    pub fn step(&mut self, world: &mut World, input: Input) -> Step {
+        if let Some(health) = &mut self.health {
+            health.invulnerable = (health.invulnerable - 1).max(0);
+        }
+        let health = self.health;
+        let (wall_candidate, mut touched_h) = world.move_h(self.actor, self.dx);
+        if wall_candidate.is_some() {
+            self.dx = 0.0;
+            self.charges.dash_timer = 0;
+        }
+        let (hit, touched_v) = world.move_v(self.actor, self.dy);
+        let pos = world.actor_pos(self.actor);
+        let medium = world.medium_at(Rect::new(pos.x, pos.y, 32.0, 32.0));
+        let impact = self.dy;
+        let mut died = false;
+        let mut bounced = false;
+        let config = self.config;
+        let mut control = config.air_control;
+        let mut friction = config.air_control;
+        // Bumping the head while launched up is not landing
+        let floor = hit.filter(|_| self.dy >= 0.0);
+        if hit.is_some() && floor.is_none() {
+            self.dy = 0.0;
+        }
+        if let Some(floor) = floor {
+            let bounce = world.solid_bounce(floor);
+            let deadly = world.solid_has_flag(floor, DEADLY) && !self.assists.deadly_immunity;
+            // Bouncy floors and soft volumes catch any fall, others hurt the faster the landing
+            let fall = if bounce.is_none() && medium.is_none() && !self.assists.fall_immunity {
+                self.dy - config.fatal_fall
+            } else {
+                0.0
+            };
+            let damage = ((fall * FALL_DAMAGE).ceil() as i32).max(0)
+                + if deadly { HAZARD_DAMAGE } else { 0 };
+            if self.hurt(damage) {
+                died = true;
+                self.dx = 0.0;
+            }
+            self.dy = match bounce {
+                Some(launch) if !died => {
+                    bounced = true;
+                    launch
+                }
+                _ if deadly && !died => -HAZARD_LAUNCH,
+                _ => 0.0,
+            };
+            (control, friction) = match world.solid_surface(floor) {
+                Some(Surface::Ice) => (ICE_CONTROL, ICE_FRICTION),
+                _ => (1.0, 1.0),
+            };
+            if let (Some(Surface::Conveyor(speed)), false) = (world.solid_surface(floor), died) {
+                touched_h = touched_h.or(world.move_h(self.actor, speed).1);
+            }
+        }
+        if let Some(medium) = medium {
+            control *= medium.control();
+        }
+        let wall = floor
+            .is_none()
+            .then_some(
+                world
+                    .collide_solids(pos + vec2(0.0, 0.05), vec2(32.0, 32.0 - 0.1))
+                    .filter(|(solid, _)| {
+                        self.assists.slide_everywhere || !world.solid_has_flag(*solid, NO_SLIDE)
+                    }),
+            )
+            .flatten();
+
+        // Wall jumps forgive leaving the wall, or pressing away from it, a few steps early
+        let grace = &mut self.grace;
+        let pressed_left = input.left && !grace.held_left;
+        let pressed_right = input.right && !grace.held_right;
+        if let Some((_, rect)) = wall {
+            grace.wall_left = rect.center().x < pos.x + 16.0;
+        }
+        let near_wall = wall.is_some() || grace.wall > 0;
+        let wall_jump = if wall.is_some() && input.right
+            || near_wall && grace.wall_left && (pressed_right || grace.right > 0)
+        {
+            Some(config.wall_jump_speed)
+        } else if wall.is_some() && input.left
+            || near_wall && !grace.wall_left && (pressed_left || grace.left > 0)
+        {
+            Some(-config.wall_jump_speed)
+        } else {
+            None
+        };
+        (grace.held_left, grace.held_right) = (input.left, input.right);
+        grace.wall = count_down(grace.wall, wall.is_some(), config.coyote_steps);
+        grace.left = count_down(grace.left, pressed_left, config.buffer_steps);
+        grace.right = count_down(grace.right, pressed_right, config.buffer_steps);
+        if wall_jump.is_some() {
+            (grace.wall, grace.left, grace.right) = (0, 0, 0);
+        }
+
+        // Landing, holding on to a wall or climbing gives back the air jump and dash
+        if floor.is_some() || wall.is_some() || self.climbing {
+            self.charges.air_jump = self.abilities.double_jump;
+            self.charges.dash = self.abilities.dash;
+        }
+        let mut jumped = false;
+        let mut dashed = false;
+        if floor.is_none() && !self.climbing {
+            let from_ledge = self.abilities.double_jump && self.grace.ledge > 0;
+            if input.jump && (self.charges.air_jump || from_ledge) {
+                // Just off a ledge it still counts as jumping from the ground
+                self.charges.air_jump &= from_ledge;
+                self.grace.ledge = 0;
+                self.charges.dash_timer = 0;
+                self.dy = -AIR_JUMP_SPEED;
+                jumped = true;
+            }
+            if input.dash && self.charges.dash {
+                self.charges.dash = false;
+                self.charges.dash_timer = DASH_STEPS;
+                let left = input.left || !input.right && self.dx < 0.0;
+                self.dx = if left { -DASH_SPEED } else { DASH_SPEED };
+                dashed = true;
+            }
+        }
+        self.grace.ledge = count_down(self.grace.ledge, floor.is_some(), config.coyote_steps);
+        let dashing = self.charges.dash_timer > 0;
+        self.charges.dash_timer = (self.charges.dash_timer - 1).max(0);
+
+        if dashing {
+            // Keeps its speed until the dash is over
+        } else if let Some(speed) = wall_jump {
+            self.dx = speed;
+        } else if input.right {
+            self.dx = (self.dx + control * config.acceleration).min(config.max_speed);
+        } else if input.left {
+            self.dx = (self.dx - control * config.acceleration).max(-config.max_speed);
+        } else if self.dx > 0.0 {
+            self.dx = (self.dx - friction * config.friction).max(0.0);
+        } else if self.dx < 0.0 {
+            self.dx = (self.dx + friction * config.friction).min(0.0);
+        }
+
+        if let Some((wall, _)) = wall {
+            let slide = match world.solid_surface(wall) {
+                Some(Surface::Sticky) => STICKY_SLIDE_SPEED,
+                _ => config.slide_speed,
+            };
+            if self.dy > 0.0 {
+                self.dy = (self.dy - config.slide_deceleration).max(slide);
+            } else {
+                self.dy = (self.dy + config.slide_deceleration).min(slide);
+            }
+        }
+
+        self.gliding = false;
+        // Holding up or down grabs a ladder or rope, stopping any fall, left or right lets go
+        let pos = world.actor_pos(self.actor);
+        let climbable = world.climbable_at(Rect::new(pos.x, pos.y, 32.0, 32.0));
+        if climbable.is_none() || input.left || input.right {
+            self.climbing = false;
+        } else if input.up || input.down {
+            self.climbing = true;
+        }
+        if let (true, Some(climbable)) = (self.climbing, climbable) {
+            // Pulled to the middle, which also carries it along with moving ropes
+            self.dx = climbable.center().x - 16.0 - pos.x;
+            self.dy = if input.up {
+                // Up to where the feet are at the top
+                (climbable.top() - pos.y - 32.0).clamp(-CLIMB_SPEED, 0.0)
+            } else if input.down {
+                CLIMB_SPEED
+            } else {
+                0.0
+            };
+        } else if dashing {
+            self.dy = 0.0;
+        } else {
+            let wind = world.wind_at(Rect::new(pos.x, pos.y, 32.0, 32.0));
+            self.dx += wind.x;
+            self.dy += GRAVITY.y + wind.y;
+            if let Some(medium) = medium {
+                if self.dy > medium.max_fall() {
+                    self.dy -= (self.dy - medium.max_fall()) * medium.damping();
+                }
+            }
+            // Gliding only slows falls, not jumps
+            self.gliding = self.abilities.glide && input.glide && floor.is_none() && self.dy > 0.0;
+            if self.gliding {
+                self.dy = self.dy.min(GLIDE_FALL);
+            }
+        }
+
+        Step {
+            floor,
+            wall,
+            touched: [touched_h, touched_v],
+            impact,
+            died,
+            hurt: !died && self.health != health,
+            bounced,
+            medium,
+            jumped,
+            dashed,
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use crate::level::*;
+use crate::storage;
+
+/// Campaign progress is kept in this file, one line per finished level and run mode, starting
+/// with `assisted` if assists helped with the record and the run mode unless it is `normal`.
+const PROGRESS_FILE: &str = "scavenger_drop_progress.txt";
+
+/// What dying costs during a run.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum RunMode {
+    /// Dying puts the scavenger back at the last soul-stone, as often as it takes.
+    #[default]
+    Normal,
+    /// The run is over once every life is lost, extra lives can be picked up.
+    Lives,
+    /// The first death ends the run.
+    Hardcore,
+    /// Diamonds stay taken through deaths.
+    NoReset,
+}
+
+impl RunMode {
+    pub const ALL: [RunMode; 4] = [
+        RunMode::Normal,
+        RunMode::Lives,
+        RunMode::Hardcore,
+        RunMode::NoReset,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RunMode::Normal => "normal",
+            RunMode::Lives => "lives",
+            RunMode::Hardcore => "hardcore",
+            RunMode::NoReset => "no_reset",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RunMode::Normal => "Normal",
+            RunMode::Lives => "Limited lives",
+            RunMode::Hardcore => "Hardcore",
+            RunMode::NoReset => "No reset",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<RunMode> {
+        RunMode::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+
+    /// The mode `by` places after this one in `ALL`, wrapping around.
+    pub fn cycle(self, by: i32) -> RunMode {
+        let index = RunMode::ALL
+            .iter()
+            .position(|mode| *mode == self)
+            .unwrap_or(0) as i32;
+        RunMode::ALL[(index + by).rem_euclid(RunMode::ALL.len() as i32) as usize]
+    }
+}
+
+/// Best results of a finished level in one run mode. Time and diamonds are kept separately,
+/// they do not have to come from the same run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    pub name: String,
+    /// Best time in steps.
+    pub time: u32,
+    pub diamonds: u32,
+    /// The time or diamonds were reached with assists on.
+    pub assisted: bool,
+    pub mode: RunMode,
+}
+
+/// Levels finished on this machine.
+#[derive(Default)]
+pub struct Progress {
+    pub records: Vec<Record>,
+}
+
+impl Progress {
+    /// Reads the progress, skipping lines that are not valid.
+    pub fn load() -> Progress {
+        storage::read(PROGRESS_FILE)
+            .map(|source| Progress::parse(&source))
+            .unwrap_or_default()
+    }
+
+    fn parse(source: &str) -> Progress {
+        let records = source
+            .lines()
+            .filter_map(|line| {
+                let mut line = line;
+                let mut assisted = false;
+                let mut mode = RunMode::Normal;
+                loop {
+                    let (word, rest) = line.split_once(' ')?;
+                    match (word, RunMode::from_name(word)) {
+                        ("assisted", _) => assisted = true,
+                        (_, Some(tag)) => mode = tag,
+                        _ => break,
+                    }
+                    line = rest;
+                }
+                let mut parts = line.splitn(3, ' ');
+                let time = parts.next()?.parse().ok()?;
+                let diamonds = parts.next()?.parse().ok()?;
+                let name = parts.next()?.to_owned();
+                Some(Record {
+                    name,
+                    time,
+                    diamonds,
+                    assisted,
+                    mode,
+                })
+            })
+            .collect();
+        Progress { records }
+    }
+
+    fn save(&self) {
+        let lines: String = self
+            .records
+            .iter()
+            .map(|record| {
+                let mut tags = String::new();
+                if record.assisted {
+                    tags.push_str("assisted ");
+                }
+                if record.mode != RunMode::Normal {
+                    tags.push_str(record.mode.name());
+                    tags.push(' ');
+                }
+                format!(
+                    "{}{} {} {}\n",
+                    tags, record.time, record.diamonds, record.name
+                )
+            })
+            .collect();
+        storage::write(PROGRESS_FILE, &lines);
+    }
+
+    pub fn record(&self, name: &str, mode: RunMode) -> Option<&Record> {
+        self.records
+            .iter()
+            .find(|record| record.name == name && record.mode == mode)
+    }
+
+    /// Whether level `name` was finished in any run mode.
+    pub fn finished(&self, name: &str) -> bool {
+        self.records.iter().any(|record| record.name == name)
+    }
+
+    /// Most diamonds of every finished level in any run mode, except for `RunMode::NoReset`,
+    /// where dying does not cost them.
+    pub fn diamonds(&self) -> u32 {
+        let mut best: Vec<(&str, u32)> = Vec::new();
+        for record in self.records.iter() {
+            if record.mode == RunMode::NoReset {
+                continue;
+            }
+            match best.iter_mut().find(|(name, _)| *name == record.name) {
+                Some((_, diamonds)) => *diamonds = (*diamonds).max(record.diamonds),
+                None => best.push((&record.name, record.diamonds)),
+            }
+        }
+        best.iter().map(|(_, diamonds)| diamonds).sum()
+    }
+
+    /// Whether every unlock rule of `level` is met.
+    pub fn unlocked(&self, level: &LevelDesc) -> bool {
+        level.unlocks.iter().all(|unlock| match unlock {
+            Unlock::Finish(name) => self.finished(name),
+            Unlock::Diamonds(count) => self.diamonds() >= *count,
+        })
+    }
+
+    /// Records reaching the ground of level `name` in `mode`, keeping the better time and
+    /// diamonds. Beating either of them with assists on tags the record as `assisted`.
+    pub fn finish(&mut self, name: &str, time: u32, diamonds: u32, assisted: bool, mode: RunMode) {
+        match self
+            .records
+            .iter_mut()
+            .find(|record| record.name == name && record.mode == mode)
+        {
+            Some(record) => {
+                if time < record.time || diamonds > record.diamonds {
+                    record.assisted |= assisted;
+                }
+                record.time = record.time.min(time);
+                record.diamonds = record.diamonds.max(diamonds);
+            }
+            None => self.records.push(Record {
+                name: name.to_owned(),
+                time,
+                diamonds,
+                assisted,
+                mode,
+            }),
+        }
+        self.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_tags_and_skips_invalid_lines() {
+        let progress = Progress::parse(
+            "900 3 The Tower\nassisted lives 1200 5 The Tower\nno_reset 800 9 The Pit\nbroken\n\
+             12 x The Chimney\n",
+        );
+        assert_eq!(progress.records.len(), 3);
+        let record = progress.record("The Tower", RunMode::Lives).unwrap();
+        assert_eq!((record.time, record.diamonds), (1200, 5));
+        assert!(record.assisted);
+        let record = progress.record("The Tower", RunMode::Normal).unwrap();
+        assert!(!record.assisted);
+        assert!(progress.record("The Pit", RunMode::NoReset).is_some());
+        assert!(!progress.finished("The Chimney"));
+    }
+
+    #[test]
+    fn diamonds_take_the_best_of_each_level_without_no_reset() {
+        let progress = Progress::parse(
+            "900 3 The Tower\nlives 1200 5 The Tower\nno_reset 800 9 The Pit\n700 2 The Chimney\n",
+        );
+        assert_eq!(progress.diamonds(), 7);
+    }
+}
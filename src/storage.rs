@@ -0,0 +1,43 @@
+/// Reads a text file from the working directory. The web build keeps its files in the local
+/// storage of the browser instead, see `storage.js`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read(name: &str) -> Option<String> {
+    std::fs::read_to_string(name).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write(name: &str, contents: &str) {
+    if let Err(err) = std::fs::write(name, contents) {
+        eprintln!("Could not save {}: {}", name, err);
+    }
+}
+
+// Implemented in storage.js
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    fn storage_length(name: *const u8, name_len: usize) -> i32;
+    fn storage_read(name: *const u8, name_len: usize, buffer: *mut u8, buffer_len: usize);
+    fn storage_write(name: *const u8, name_len: usize, contents: *const u8, contents_len: usize);
+}
+
+/// Checked by the JS bundle against the version of the `storage` plugin.
+#[cfg(target_arch = "wasm32")]
+#[no_mangle]
+pub extern "C" fn storage_crate_version() -> u32 {
+    1
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read(name: &str) -> Option<String> {
+    // SAFETY: storage.js only reads and writes within the slices passed to it
+    let length = unsafe { storage_length(name.as_ptr(), name.len()) };
+    let mut buffer = vec![0; usize::try_from(length).ok()?];
+    unsafe { storage_read(name.as_ptr(), name.len(), buffer.as_mut_ptr(), buffer.len()) };
+    String::from_utf8(buffer).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write(name: &str, contents: &str) {
+    // SAFETY: storage.js only reads within the slices passed to it
+    unsafe { storage_write(name.as_ptr(), name.len(), contents.as_ptr(), contents.len()) };
+}
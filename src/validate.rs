@@ -0,0 +1,834 @@
+use crate::controller::ControllerConfig;
+use crate::level::*;
+use crate::motion::Playback;
+use crate::physics::*;
+use crate::player::*;
+use macroquad::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+
+/// Steps an input is held at least before the search branches again.
+const MIN_STEPS_PER_INPUT: u32 = 8;
+/// An input is held until the quantized state changes, but at most this many steps.
+const MAX_STEPS_PER_INPUT: u32 = 60;
+/// Size of the grid airborne states are merged on, standing states use a finer one.
+const AIR_CELL: f32 = 16.0;
+/// Moving platforms further away than this do not distinguish search states.
+const PHASE_RADIUS: f32 = 256.0;
+/// Same for standing states, which need to see a ferry coming from further away.
+const WAIT_RADIUS: f32 = 1200.0;
+/// Most steps two moving platforms are checked for overlaps, ten minutes of play.
+const MAX_OVERLAP_STEPS: u32 = 36_000;
+
+const NO_INPUT: Input = Input {
+    left: false,
+    right: false,
+    up: false,
+    down: false,
+    jump: false,
+    dash: false,
+    glide: false,
+};
+
+const INPUTS: [Input; 3] = [
+    NO_INPUT,
+    Input {
+        left: true,
+        ..NO_INPUT
+    },
+    Input {
+        right: true,
+        ..NO_INPUT
+    },
+];
+
+/// Only tried in levels with ladders or ropes.
+const CLIMB_INPUTS: [Input; 2] = [
+    Input {
+        up: true,
+        ..NO_INPUT
+    },
+    Input {
+        down: true,
+        ..NO_INPUT
+    },
+];
+
+/// Only tried when the scavenger starts with the ability.
+const JUMP_INPUT: Input = Input {
+    jump: true,
+    ..NO_INPUT
+};
+const GLIDE_INPUT: Input = Input {
+    glide: true,
+    ..NO_INPUT
+};
+const DASH_INPUTS: [Input; 2] = [
+    Input {
+        left: true,
+        dash: true,
+        ..NO_INPUT
+    },
+    Input {
+        right: true,
+        dash: true,
+        ..NO_INPUT
+    },
+];
+
+pub struct Options {
+    pub seed: u64,
+    pub max_states: usize,
+    /// More places the search starts from besides the level start, each counts as reachable.
+    pub starts: Vec<Vec2>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            seed: 0,
+            max_states: 8_000_000,
+            starts: Vec::new(),
+        }
+    }
+}
+
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// Platforms are referred to by their index in `LevelDesc::platforms`.
+pub enum Finding {
+    /// Two solids overlap, `step` is the first step this happens at.
+    Overlap {
+        a: usize,
+        b: usize,
+        step: u32,
+    },
+    /// Two platforms only repeat how they are placed after more than `MAX_OVERLAP_STEPS`
+    /// steps, they were not checked for overlaps after that.
+    OverlapUnchecked {
+        a: usize,
+        b: usize,
+        steps: u32,
+    },
+    UnreachableCoin {
+        position: Vec2,
+    },
+    GoalUnreachable,
+    /// The scavenger can fall below every solid, `position` is where it leaves the level.
+    OutOfBounds {
+        position: Vec2,
+    },
+    /// The scavenger can stand on the platform, but can only leave it by dying.
+    DeathOnly {
+        platform: usize,
+    },
+    /// The scavenger can stand on the platform, but can neither reach the goal nor die from there.
+    Softlock {
+        platform: usize,
+    },
+}
+
+impl Finding {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Finding::Overlap { .. } | Finding::OverlapUnchecked { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+pub struct Report {
+    pub findings: Vec<Finding>,
+    pub states: usize,
+    /// False if the search hit `Options::max_states` before exploring everything.
+    pub complete: bool,
+}
+
+#[derive(Copy, Clone)]
+struct State {
+    pos: Vec2,
+    dx: f32,
+    dy: f32,
+    t: u32,
+    standing: bool,
+    climbing: bool,
+    charges: Charges,
+}
+
+enum Outcome {
+    Died,
+    Goal,
+    OutOfBounds(Vec2),
+    Moved(State, Option<Solid>),
+}
+
+struct Timeline {
+    solid: Solid,
+    positions: Vec<Vec2>,
+    /// Starts over after the last position, otherwise stays there.
+    repeats: bool,
+}
+
+impl Timeline {
+    fn at(&self, t: u32) -> Vec2 {
+        if self.repeats {
+            self.positions[t as usize % self.positions.len()]
+        } else {
+            self.positions[(t as usize).min(self.positions.len() - 1)]
+        }
+    }
+}
+
+struct Coin {
+    offset: Vec2,
+    carrier: Option<usize>,
+}
+
+struct Search {
+    world: World,
+    platforms: Vec<Platform>,
+    timelines: Vec<Option<Timeline>>,
+    coins: Vec<Coin>,
+    coins_taken: Vec<bool>,
+    scavenger: Scavenger,
+    /// Where each platform of the level starts and how large it is.
+    layout: Vec<Rect>,
+    /// Lowest point of any solid, the scavenger is lost once it falls past it.
+    bottom: f32,
+}
+
+impl Search {
+    fn new(level: &LevelDesc, seed: u64) -> Self {
+        let mut world = World::new();
+        let mut platforms = level.build(&mut world);
+
+        let max_period = platforms.iter().map(Platform::period).max().unwrap_or(0);
+        let mut timelines: Vec<_> = platforms
+            .iter()
+            .map(|platform| {
+                // Triggered platforms and those of paused sync groups depend on the scavenger,
+                // they are checked as if they never moved. The same goes for crumbling ones,
+                // which never give way here, and doors, which stay as their channel starts.
+                let paused = platform
+                    .group
+                    .is_some_and(|group| level.groups[group].starts_paused);
+                let moving = platform.trigger == Trigger::Always && !paused;
+                (platform.period() > 0 && moving).then(|| Timeline {
+                    solid: platform.solid,
+                    positions: Vec::new(),
+                    repeats: platform.playback != Playback::Once,
+                })
+            })
+            .collect();
+        for t in 0..=max_period {
+            for (platform, timeline) in platforms.iter_mut().zip(timelines.iter_mut()) {
+                if let Some(timeline) = timeline {
+                    // Platforms that stop also need where they end up
+                    if t < platform.period() || t == platform.period() && !timeline.repeats {
+                        timeline.positions.push(world.solid_pos(platform.solid));
+                    }
+                }
+                platform.step(&mut world);
+            }
+        }
+        for platform in platforms.iter_mut() {
+            platform.reset(&mut world);
+        }
+
+        let coins = level
+            .build_coins(&mut world, &platforms, seed)
+            .into_iter()
+            .map(|coin| {
+                let position = world.actor_pos(coin);
+                let carrier = platforms.iter().position(|platform| {
+                    let rect = world.solid_collider(platform.solid).as_rect();
+                    rect.y == position.y + 32.0
+                        && rect.left() <= position.x
+                        && rect.right() >= position.x
+                });
+                let offset = match carrier {
+                    Some(carrier) => position - world.solid_pos(platforms[carrier].solid),
+                    None => position,
+                };
+                world.set_actor_pos(coin, vec2(f32::MIN, f32::MIN));
+                Coin { offset, carrier }
+            })
+            .collect::<Vec<_>>();
+
+        let bottom = platforms
+            .iter()
+            .zip(timelines.iter())
+            .flat_map(|(platform, timeline)| {
+                let collider = world.solid_collider(platform.solid);
+                let positions = match timeline {
+                    Some(timeline) => timeline.positions.as_slice(),
+                    None => std::slice::from_ref(&platform.initial_position),
+                };
+                positions
+                    .iter()
+                    .map(move |position| position.y + collider.dimension.y)
+            })
+            .fold(level.start.y, f32::max);
+
+        let mut scavenger = Scavenger::new(&mut world, level.start);
+        scavenger.abilities = level.abilities;
+        // Levels have to be beatable without coyote time or buffered presses
+        scavenger.config = ControllerConfig {
+            coyote_steps: 0,
+            buffer_steps: 0,
+            ..level.controller
+        };
+        Search {
+            bottom,
+            world,
+            platforms,
+            timelines,
+            coins_taken: vec![false; coins.len()],
+            coins,
+            scavenger,
+            layout: level.platforms.iter().map(PlatformDesc::rect).collect(),
+        }
+    }
+
+    fn platform_pos(&self, index: usize, t: u32) -> Vec2 {
+        match &self.timelines[index] {
+            Some(timeline) => timeline.at(t),
+            None => self.layout[index].point(),
+        }
+    }
+
+    fn coin_rect(&self, coin: &Coin, t: u32) -> Rect {
+        let position = match coin.carrier {
+            Some(carrier) => self.platform_pos(carrier, t) + coin.offset,
+            None => coin.offset,
+        };
+        Rect::new(position.x, position.y, 32.0, 32.0)
+    }
+
+    /// Hash of the quantized state and the moving platforms near it,
+    /// so waiting for a platform creates new states.
+    fn key(&self, state: &State) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        let (cell, radius) = if state.standing {
+            (8.0, WAIT_RADIUS)
+        } else {
+            (AIR_CELL, PHASE_RADIUS)
+        };
+        (state.pos / cell).round().as_ivec2().hash(&mut hasher);
+        vec2(state.dx, state.dy)
+            .round()
+            .as_ivec2()
+            .hash(&mut hasher);
+        state.climbing.hash(&mut hasher);
+        state.charges.hash(&mut hasher);
+        for (index, timeline) in self.timelines.iter().enumerate() {
+            let Some(timeline) = timeline else {
+                continue;
+            };
+            let platform_pos = timeline.at(state.t);
+            let size = self.layout[index].size();
+            let nearest = state.pos.clamp(platform_pos, platform_pos + size);
+            if nearest.distance(state.pos) < radius {
+                index.hash(&mut hasher);
+                (platform_pos / 32.0).round().as_ivec2().hash(&mut hasher);
+            }
+        }
+        // Waiting for a gust to die down also creates new states
+        let rect = Rect::new(state.pos.x, state.pos.y, 32.0, 32.0);
+        for (index, wind) in self.world.winds().iter().enumerate() {
+            if wind.gust != Gust::Constant && wind.rect.overlaps(&rect) {
+                index.hash(&mut hasher);
+                ((wind.strength(state.t) * 4.0).round() as i32).hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+
+    fn simulate(&mut self, state: State, input: Input) -> Outcome {
+        for timeline in self.timelines.iter().flatten() {
+            self.world
+                .set_solid_pos(timeline.solid, timeline.at(state.t));
+        }
+        self.world.set_actor_pos(self.scavenger.actor, state.pos);
+        self.scavenger.dx = state.dx;
+        self.scavenger.dy = state.dy;
+        self.scavenger.climbing = state.climbing;
+        self.scavenger.charges = state.charges;
+        self.world.set_wind_clock(state.t);
+        let start_key = self.key(&state);
+        for t in state.t..state.t + MAX_STEPS_PER_INPUT {
+            let step = self.scavenger.step(&mut self.world, input);
+            let pos = self.world.actor_pos(self.scavenger.actor);
+            let rect = Rect::new(pos.x, pos.y, 32.0, 32.0);
+            for index in 0..self.coins.len() {
+                if !self.coins_taken[index] && self.coin_rect(&self.coins[index], t).overlaps(&rect)
+                {
+                    self.coins_taken[index] = true;
+                }
+            }
+            if step.died {
+                return Outcome::Died;
+            }
+            if rect.y > self.bottom {
+                return Outcome::OutOfBounds(rect.point());
+            }
+            if let Some(floor) = step.floor {
+                if self.world.solid_has_flag(floor, GROUND_LEVEL) {
+                    return Outcome::Goal;
+                }
+            }
+            for timeline in self.timelines.iter().flatten() {
+                let delta = timeline.at(t + 1) - timeline.at(t);
+                self.world.solid_move(timeline.solid, delta);
+            }
+            self.world.step_wind();
+            let next = State {
+                pos: self.world.actor_pos(self.scavenger.actor),
+                dx: self.scavenger.dx,
+                dy: self.scavenger.dy,
+                t: t + 1,
+                standing: step.floor.is_some(),
+                climbing: self.scavenger.climbing,
+                charges: self.scavenger.charges,
+            };
+            let steps = next.t - state.t;
+            if steps >= MIN_STEPS_PER_INPUT && self.key(&next) != start_key
+                || steps == MAX_STEPS_PER_INPUT
+            {
+                return Outcome::Moved(next, step.floor);
+            }
+        }
+        unreachable!()
+    }
+
+    /// Steps until the platform moves in a cycle, and the steps of that cycle.
+    fn repetition(&self, index: usize) -> (u64, u64) {
+        match &self.timelines[index] {
+            Some(timeline) if timeline.repeats => (0, timeline.positions.len() as u64),
+            Some(timeline) => (timeline.positions.len() as u64, 1),
+            None => (0, 1),
+        }
+    }
+
+    /// Area the platform covers at any step.
+    fn sweep(&self, index: usize) -> Rect {
+        let size = self.layout[index].size();
+        match &self.timelines[index] {
+            Some(timeline) => timeline
+                .positions
+                .iter()
+                .map(|&position| platform_rect(position, size))
+                .reduce(|sweep, rect| sweep.combine_with(rect))
+                .unwrap_or(self.layout[index]),
+            None => self.layout[index],
+        }
+    }
+
+    fn overlaps(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let layout = &self.layout;
+        let sweeps: Vec<Rect> = (0..layout.len()).map(|index| self.sweep(index)).collect();
+        for a in 0..layout.len() {
+            for b in a + 1..layout.len() {
+                if !overlapping(sweeps[a], sweeps[b]) {
+                    continue;
+                }
+                // Once platforms that stop have stopped, the pair repeats after both periods
+                let (lead_a, period_a) = self.repetition(a);
+                let (lead_b, period_b) = self.repetition(b);
+                let period = lead_a.max(lead_b) + period_a / gcd(period_a, period_b) * period_b;
+                let steps = period.min(MAX_OVERLAP_STEPS as u64) as u32;
+                let step = (0..steps).find(|&t| {
+                    overlapping(
+                        platform_rect(self.platform_pos(a, t), layout[a].size()),
+                        platform_rect(self.platform_pos(b, t), layout[b].size()),
+                    )
+                });
+                if let Some(step) = step {
+                    findings.push(Finding::Overlap { a, b, step });
+                } else if period > steps as u64 {
+                    findings.push(Finding::OverlapUnchecked { a, b, steps });
+                }
+            }
+        }
+        findings
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Whether `a` and `b` share some area, touching edges do not count.
+fn overlapping(a: Rect, b: Rect) -> bool {
+    a.intersect(b)
+        .is_some_and(|rect| rect.w > 0.0 && rect.h > 0.0)
+}
+
+fn platform_rect(position: Vec2, size: Vec2) -> Rect {
+    Rect::new(position.x, position.y, size.x, size.y)
+}
+
+/// Explores the states the scavenger can reach from the level start using the game physics
+/// and reports coins, platforms and the goal that cannot be reached without dying.
+/// Enemies, falling rocks, switches and pickups are left out, they would make the search
+/// depend on timing it does not track. Levels in the health mode are checked as if any hard
+/// landing was fatal.
+pub fn validate(level: &LevelDesc, options: &Options) -> Report {
+    let mut validation = Validation::new(level, options);
+    while !validation.explore(usize::MAX) {}
+    validation.finish()
+}
+
+/// The search of `validate`, run a few states at a time so it can be spread over frames.
+pub struct Validation {
+    search: Search,
+    solid_index: HashMap<Solid, usize>,
+    inputs: Vec<Input>,
+    max_states: usize,
+    /// Extra starts, taken once everything reachable from the level start is explored.
+    starts: Vec<Vec2>,
+    states: Vec<State>,
+    seen: HashMap<u64, u32>,
+    edges: Vec<(u32, u32)>,
+    reaches_goal: Vec<bool>,
+    dies: Vec<bool>,
+    standing_on: Vec<Option<usize>>,
+    truncated: Vec<bool>,
+    out_of_bounds: Option<Vec2>,
+    queue: VecDeque<u32>,
+    /// Coins taken, whether the goal was reached and where the scavenger left the level, from
+    /// the level start only. What the extra starts add counts for the platforms alone.
+    reached: Option<(Vec<bool>, bool, Option<Vec2>)>,
+}
+
+impl Validation {
+    pub fn new(level: &LevelDesc, options: &Options) -> Self {
+        let search = Search::new(level, options.seed);
+        let solid_index = search
+            .platforms
+            .iter()
+            .enumerate()
+            .map(|(index, platform)| (platform.solid, index))
+            .collect();
+
+        let mut inputs = INPUTS.to_vec();
+        if !level.climbables.is_empty() {
+            inputs.extend(CLIMB_INPUTS);
+        }
+        if level.abilities.double_jump {
+            inputs.push(JUMP_INPUT);
+        }
+        if level.abilities.glide {
+            inputs.push(GLIDE_INPUT);
+        }
+        if level.abilities.dash {
+            inputs.extend(DASH_INPUTS);
+        }
+        let mut validation = Validation {
+            search,
+            solid_index,
+            inputs,
+            max_states: options.max_states,
+            starts: options.starts.clone(),
+            states: Vec::new(),
+            seen: HashMap::new(),
+            edges: Vec::new(),
+            reaches_goal: Vec::new(),
+            dies: Vec::new(),
+            standing_on: Vec::new(),
+            truncated: Vec::new(),
+            out_of_bounds: None,
+            queue: VecDeque::new(),
+            reached: None,
+        };
+        validation.start(level.start);
+        validation
+    }
+
+    fn start(&mut self, pos: Vec2) {
+        let start = State {
+            pos,
+            dx: 0.0,
+            dy: 1.0,
+            t: 0,
+            standing: false,
+            climbing: false,
+            charges: Charges::default(),
+        };
+        let key = self.search.key(&start);
+        if !self.seen.contains_key(&key) {
+            self.add(key, start, None);
+        }
+    }
+
+    fn add(&mut self, key: u64, state: State, standing_on: Option<usize>) -> u32 {
+        let index = self.states.len() as u32;
+        self.seen.insert(key, index);
+        self.states.push(state);
+        self.reaches_goal.push(false);
+        self.dies.push(false);
+        self.standing_on.push(standing_on);
+        self.truncated.push(false);
+        self.queue.push_back(index);
+        index
+    }
+
+    /// Explores up to `budget` more states, true once there is nothing left to explore.
+    pub fn explore(&mut self, budget: usize) -> bool {
+        for _ in 0..budget {
+            let Some(from) = self.queue.pop_front() else {
+                if self.reached.is_some() {
+                    return true;
+                }
+                self.reached = Some((
+                    self.search.coins_taken.clone(),
+                    self.reaches_goal.contains(&true),
+                    self.out_of_bounds,
+                ));
+                for pos in std::mem::take(&mut self.starts) {
+                    self.start(pos);
+                }
+                continue;
+            };
+            for index in 0..self.inputs.len() {
+                match self.search.simulate(self.states[from as usize], self.inputs[index]) {
+                    Outcome::Died => self.dies[from as usize] = true,
+                    Outcome::Goal => self.reaches_goal[from as usize] = true,
+                    Outcome::OutOfBounds(position) => {
+                        self.out_of_bounds.get_or_insert(position);
+                    }
+                    Outcome::Moved(state, standing) => {
+                        let key = self.search.key(&state);
+                        let to = match self.seen.get(&key) {
+                            Some(&to) => to,
+                            None if self.states.len() < self.max_states => {
+                                let standing_on = standing
+                                    .and_then(|solid| self.solid_index.get(&solid))
+                                    .copied();
+                                self.add(key, state, standing_on)
+                            }
+                            None => {
+                                self.truncated[from as usize] = true;
+                                continue;
+                            }
+                        };
+                        self.edges.push((from, to));
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Reports what the search found, `explore` has to be done first.
+    pub fn finish(self) -> Report {
+        let Validation {
+            search,
+            states,
+            seen,
+            edges,
+            reaches_goal,
+            dies,
+            standing_on,
+            truncated,
+            reached,
+            ..
+        } = self;
+        drop(seen);
+        let (coins_taken, goal_reached, out_of_bounds) = reached.unwrap();
+        let complete = !truncated.contains(&true);
+
+        // States with unexplored successors might lead anywhere, give them the benefit of the doubt.
+        let incoming = Incoming::new(edges, states.len());
+        let can_goal = incoming.propagate_back(&reaches_goal, &truncated);
+        let can_die = incoming.propagate_back(&dies, &truncated);
+
+        let mut findings = search.overlaps();
+        for (index, coin) in search.coins.iter().enumerate() {
+            if complete && !coins_taken[index] {
+                findings.push(Finding::UnreachableCoin {
+                    position: search.coin_rect(coin, 0).point(),
+                });
+            }
+        }
+        if complete && !goal_reached {
+            findings.push(Finding::GoalUnreachable);
+        }
+        if let Some(position) = out_of_bounds {
+            findings.push(Finding::OutOfBounds { position });
+        }
+        let mut platform_states = vec![(false, false, false); search.layout.len()];
+        for (state, platform) in standing_on.iter().enumerate() {
+            if let Some(platform) = *platform {
+                let (stands, goal, die) = &mut platform_states[platform];
+                *stands = true;
+                *goal |= can_goal[state];
+                *die |= can_die[state];
+            }
+        }
+        for (platform, (stands, goal, die)) in platform_states.into_iter().enumerate() {
+            if !stands || goal {
+                continue;
+            }
+            findings.push(if die {
+                Finding::DeathOnly { platform }
+            } else {
+                Finding::Softlock { platform }
+            });
+        }
+
+        Report {
+            findings,
+            states: states.len(),
+            complete,
+        }
+    }
+}
+
+/// Predecessors of every search state, stored as one slice per state.
+struct Incoming {
+    offsets: Vec<u32>,
+    sources: Vec<u32>,
+}
+
+impl Incoming {
+    fn new(mut edges: Vec<(u32, u32)>, states: usize) -> Self {
+        edges.sort_unstable_by_key(|&(_, to)| to);
+        let mut offsets = vec![0; states + 1];
+        for &(_, to) in edges.iter() {
+            offsets[to as usize + 1] += 1;
+        }
+        for state in 0..states {
+            offsets[state + 1] += offsets[state];
+        }
+        Incoming {
+            offsets,
+            sources: edges.into_iter().map(|(from, _)| from).collect(),
+        }
+    }
+
+    /// Marks every state that can reach a marked or truncated state.
+    fn propagate_back(&self, marked: &[bool], truncated: &[bool]) -> Vec<bool> {
+        let mut result: Vec<bool> = marked
+            .iter()
+            .zip(truncated)
+            .map(|(&marked, &truncated)| marked || truncated)
+            .collect();
+        let mut queue: VecDeque<_> = (0..result.len()).filter(|&state| result[state]).collect();
+        while let Some(to) = queue.pop_front() {
+            let sources = self.offsets[to] as usize..self.offsets[to + 1] as usize;
+            for &from in &self.sources[sources] {
+                if !result[from as usize] {
+                    result[from as usize] = true;
+                    queue.push_back(from as usize);
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A ledge above the ground between two walls.
+    const ROOM: &str = "start 0 0\nplatform -48 32 96 32 no_slide\n\
+                        platform -400 160 800 32 ground|no_slide\n\
+                        platform -400 -400 16 560 no_slide\nplatform 384 -400 16 560 no_slide\n";
+
+    fn errors(report: &Report) -> usize {
+        report
+            .findings
+            .iter()
+            .filter(|finding| matches!(finding.severity(), Severity::Error))
+            .count()
+    }
+
+    #[test]
+    fn reachable_level_has_no_errors() {
+        let mut level = LevelDesc::parse(ROOM).unwrap();
+        level.coins = Some(vec![vec2(200.0, 140.0)]);
+        let report = validate(&level, &Options::default());
+        assert!(report.complete);
+        assert_eq!(errors(&report), 0);
+    }
+
+    #[test]
+    fn finds_unreachable_coins() {
+        let mut level = LevelDesc::parse(ROOM).unwrap();
+        level.coins = Some(vec![vec2(200.0, 140.0), vec2(0.0, -300.0)]);
+        let report = validate(&level, &Options::default());
+        assert!(report.complete);
+        assert_eq!(errors(&report), 1);
+        assert!(report.findings.iter().any(|finding| matches!(
+            finding,
+            Finding::UnreachableCoin { position } if *position == vec2(0.0, -300.0)
+        )));
+    }
+
+    #[test]
+    fn finds_softlocks() {
+        // A pit in the ground too deep to jump out of
+        let source = "start 0 0\nplatform -48 32 96 32 no_slide\n\
+                      platform -400 160 496 128 ground|no_slide\n\
+                      platform 208 160 192 128 ground|no_slide\nplatform 96 288 112 16 no_slide\n\
+                      platform -400 -400 16 560 no_slide\nplatform 384 -400 16 560 no_slide\n";
+        let mut level = LevelDesc::parse(source).unwrap();
+        level.coins = Some(Vec::new());
+        let report = validate(&level, &Options::default());
+        assert!(report.complete);
+        assert_eq!(errors(&report), 1);
+        assert!(matches!(
+            report.findings[..],
+            [Finding::Softlock { platform: 3 }]
+        ));
+    }
+
+    /// Run with `cargo test --release -- --ignored`, or check single files with `validate-level`.
+    #[test]
+    #[ignore = "searches every state of the level files, which takes minutes"]
+    fn level_files_pass() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/levels");
+        let mut checked = 0;
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|extension| extension != "lvl") {
+                continue;
+            }
+            let source = std::fs::read_to_string(&path).unwrap();
+            let level = LevelDesc::parse(&source).unwrap();
+            let report = validate(&level, &Options::default());
+            assert!(report.complete, "{}", path.display());
+            assert_eq!(errors(&report), 0, "{}", path.display());
+            checked += 1;
+        }
+        assert_eq!(checked, LEVELS.len());
+    }
+
+    #[test]
+    fn finds_overlaps_after_the_longer_period() {
+        // Two platforms jump to the same place every 120 and 90 steps, first together at 359
+        let source = format!(
+            "{}platform 208 -304 32 16 -\n  pause 118\n  move 208 -240 1\n  move 208 -304 1\n\
+             platform 208 -96 32 16 -\n  pause 88\n  move 208 -240 1\n  move 208 -96 1\n",
+            ROOM
+        );
+        let mut level = LevelDesc::parse(&source).unwrap();
+        level.coins = Some(Vec::new());
+        let report = validate(&level, &Options::default());
+        assert!(report.findings.iter().any(|finding| matches!(
+            finding,
+            Finding::Overlap { a: 4, b: 5, step } if *step > 120
+        )));
+    }
+}
@@ -0,0 +1,36 @@
+// Keeps the files of src/storage.rs in the local storage of the browser, so progress, daily
+// attempts and tuning last across page loads.
+miniquad_add_plugin({
+    name: "storage",
+    version: 1,
+    register_plugin: function (importObject) {
+        const bytes = () => new Uint8Array(wasm_memory.buffer);
+        const text = (ptr, len) => new TextDecoder().decode(bytes().subarray(ptr, ptr + len));
+        // Local storage throws when it is disabled, then nothing is kept
+        const load = (name) => {
+            try {
+                return localStorage.getItem(name);
+            } catch (err) {
+                console.warn(err);
+                return null;
+            }
+        };
+
+        // Length of the file in bytes, -1 if there is none
+        importObject.env.storage_length = function (name_ptr, name_len) {
+            const contents = load(text(name_ptr, name_len));
+            return contents === null ? -1 : new TextEncoder().encode(contents).length;
+        };
+        importObject.env.storage_read = function (name_ptr, name_len, buffer_ptr, buffer_len) {
+            const contents = new TextEncoder().encode(load(text(name_ptr, name_len)) ?? "");
+            bytes().set(contents.subarray(0, buffer_len), buffer_ptr);
+        };
+        importObject.env.storage_write = function (name_ptr, name_len, contents_ptr, contents_len) {
+            try {
+                localStorage.setItem(text(name_ptr, name_len), text(contents_ptr, contents_len));
+            } catch (err) {
+                console.warn(err);
+            }
+        };
+    },
+});